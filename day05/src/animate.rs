@@ -0,0 +1,189 @@
+use crate::{Crane, Move, Part, Stacks};
+use std::io::prelude::*;
+use std::time::{Duration, Instant};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::{clear, color, cursor};
+
+const MIN_DELAY: Duration = Duration::from_millis(10);
+const MAX_DELAY: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+enum Control {
+    Step,
+    Redraw,
+    Quit,
+}
+
+struct Player {
+    delay: Duration,
+    paused: bool,
+    step: usize,
+    total: usize,
+}
+
+impl Player {
+    fn status(&self, last: Option<&Move>) -> Vec<String> {
+        let last = last.map(|m| m.to_string()).unwrap_or_default();
+        let state = if self.paused { "paused" } else { "playing" };
+        vec![
+            format!(
+                "step {}/{}  {}ms  {}  {}",
+                self.step,
+                self.total,
+                self.delay.as_millis(),
+                state,
+                last
+            ),
+            "space: pause/resume  n: step  +/-: faster/slower  q: quit".to_string(),
+        ]
+    }
+
+    /// Wait for the delay to pass or for the user to press a key
+    fn wait(
+        &mut self,
+        keys: &mut impl Iterator<Item = std::io::Result<Key>>,
+    ) -> anyhow::Result<Control> {
+        let start = Instant::now();
+        loop {
+            for key in keys.by_ref() {
+                match key? {
+                    Key::Char('q') | Key::Esc | Key::Ctrl('c') => return Ok(Control::Quit),
+                    Key::Char(' ') => {
+                        self.paused = !self.paused;
+                        return Ok(Control::Redraw);
+                    }
+                    Key::Char('n') | Key::Right => return Ok(Control::Step),
+                    Key::Char('+') => {
+                        self.delay = (self.delay / 2).max(MIN_DELAY);
+                        return Ok(Control::Redraw);
+                    }
+                    Key::Char('-') => {
+                        self.delay = (self.delay * 2).min(MAX_DELAY);
+                        return Ok(Control::Redraw);
+                    }
+                    _ => {}
+                }
+            }
+            if !self.paused && start.elapsed() >= self.delay {
+                return Ok(Control::Step);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+fn paint(text: String, fg: Option<String>) -> String {
+    match fg {
+        Some(fg) => format!("{}{}{}", fg, text, color::Fg(color::Reset)),
+        None => text,
+    }
+}
+
+/// Render the stacks in the input drawing format, highlighting the crates moved by the last
+/// instruction in red and its source and destination column numbers in yellow and green.
+pub fn frame(stacks: &Stacks, last: Option<&Move>) -> Vec<String> {
+    let last = match last {
+        Some(last) => last,
+        None => return stacks.draw(None),
+    };
+    let highlight = |part, text| match part {
+        Part::Crate { col, row }
+            if last.to_col == col && row + last.count >= stacks.columns[col - 1].len() =>
+        {
+            paint(text, Some(color::Fg(color::Red).to_string()))
+        }
+        Part::Label(n) if last.from_col == n => {
+            paint(text, Some(color::Fg(color::Yellow).to_string()))
+        }
+        Part::Label(n) if last.to_col == n => {
+            paint(text, Some(color::Fg(color::Green).to_string()))
+        }
+        _ => text,
+    };
+    stacks.draw(Some(&highlight))
+}
+
+fn draw(
+    screen: &mut impl Write,
+    player: &Player,
+    stacks: &Stacks,
+    last: Option<&Move>,
+) -> anyhow::Result<()> {
+    write!(screen, "{}{}", clear::All, cursor::Goto(1, 1))?;
+    let mut lines = player.status(last);
+    lines.push(String::new());
+    lines.extend(frame(stacks, last));
+    // raw mode does not translate \n into a carriage return
+    write!(screen, "{}\r\n", lines.join("\r\n"))?;
+    screen.flush()?;
+    Ok(())
+}
+
+/// Replay the moves in the terminal, redrawing the stacks after each one.
+///
+/// Playback can be paused with space, stepped with `n`, sped up or slowed down with `+` and `-`
+/// and stopped early with `q`. The stacks are left in whatever state the replay reached.
+pub fn animate(
+    stacks: &mut Stacks,
+    moves: &[Move],
+    crane: Crane,
+    delay: Duration,
+) -> anyhow::Result<()> {
+    let mut screen = std::io::stdout().into_raw_mode()?;
+    let mut keys = termion::async_stdin().keys();
+    let mut player = Player {
+        delay: delay.clamp(MIN_DELAY, MAX_DELAY),
+        paused: false,
+        step: 0,
+        total: moves.len(),
+    };
+    write!(screen, "{}", cursor::Hide)?;
+    let mut last = None;
+    let result = (|| {
+        draw(&mut screen, &player, stacks, last)?;
+        while player.step < moves.len() {
+            match player.wait(&mut keys)? {
+                Control::Quit => break,
+                Control::Redraw => {}
+                Control::Step => {
+                    let instruction = &moves[player.step];
+                    stacks.apply(crane, instruction)?;
+                    player.step += 1;
+                    last = Some(instruction);
+                }
+            }
+            draw(&mut screen, &player, stacks, last)?;
+        }
+        Ok(())
+    })();
+    write!(screen, "{}", cursor::Show)?;
+    screen.flush()?;
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_move_instructions, parse_stacks};
+
+    #[test]
+    fn test_frame() {
+        let example = include_str!("../example.txt");
+        let mut reader = example.as_bytes();
+        let mut stacks = parse_stacks(&mut reader).unwrap();
+        let moves = parse_move_instructions(&mut reader).unwrap();
+        assert_eq!(frame(&stacks, None).join("\n"), stacks.to_string());
+
+        stacks.apply(Crane::CrateMover9000, &moves[0]).unwrap();
+        stacks.apply(Crane::CrateMover9000, &moves[1]).unwrap();
+        let lines = frame(&stacks, Some(&moves[1]));
+        let red = color::Fg(color::Red).to_string();
+        assert_eq!(
+            lines.iter().map(|l| l.matches(&red).count()).sum::<usize>(),
+            3
+        );
+        assert!(lines[0].ends_with(&format!("{}[Z]{}", red, color::Fg(color::Reset))));
+    }
+}
@@ -0,0 +1,62 @@
+//! Find a shortest list of move instructions turning one stack drawing into another.
+//!
+//! Usage: `d5infer [--crane 9000|9001] [--max-memory <MiB> | --max-states <n>] <start> <target>`
+//!
+//! The search stops with an error once the states it has seen would take more than
+//! `--max-memory`, 1024 MiB by default, or once it has seen `--max-states` of them.
+use anyhow::anyhow as e;
+use day05::search::{shortest_moves, states_within};
+use day05::{parse_stacks, Crane};
+use std::fs::File;
+use std::io::BufReader;
+
+fn read_stacks(filename: &str) -> anyhow::Result<day05::Stacks> {
+    let f = File::open(filename)?;
+    parse_stacks(&mut BufReader::new(f))
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut crane = Crane::CrateMover9000;
+    let mut max_memory: usize = 1024;
+    let mut max_states = None;
+    let mut filenames = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--crane" => {
+                crane = args
+                    .next()
+                    .ok_or_else(|| e!("--crane requires a value"))?
+                    .parse()?
+            }
+            "--max-memory" => {
+                max_memory = args
+                    .next()
+                    .ok_or_else(|| e!("--max-memory requires a value"))?
+                    .parse()?
+            }
+            "--max-states" => {
+                max_states = Some(
+                    args.next()
+                        .ok_or_else(|| e!("--max-states requires a value"))?
+                        .parse()?,
+                )
+            }
+            _ => filenames.push(arg),
+        }
+    }
+    let (start, target) = match &filenames[..] {
+        [start, target] => (read_stacks(start)?, read_stacks(target)?),
+        _ => return Err(e!("expected a start and a target drawing")),
+    };
+    let max_states = max_states.unwrap_or_else(|| states_within(&start, max_memory << 20));
+    match shortest_moves(&start, &target, crane, max_states)? {
+        Some(moves) => {
+            for instruction in moves {
+                println!("{}", instruction);
+            }
+        }
+        None => return Err(e!("target cannot be reached with the {:?}", crane)),
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use crate::{Crane, Move, Stacks};
+use anyhow::anyhow as e;
+
+/// A move that has been carried out along with the crates it picked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub instruction: Move,
+    /// The crates taken off the source stack, bottom crate first
+    pub crates: Vec<char>,
+}
+
+/// Applies moves to a set of stacks while keeping enough history to undo and redo them.
+///
+/// Every move is checked before any crate is touched so a move that cannot be carried out in
+/// full leaves both the stacks and the journal as they were.
+pub struct Journal {
+    stacks: Stacks,
+    crane: Crane,
+    applied: Vec<Entry>,
+    undone: Vec<Entry>,
+}
+
+impl Journal {
+    pub fn new(stacks: Stacks, crane: Crane) -> Self {
+        Self {
+            stacks,
+            crane,
+            applied: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    pub fn into_stacks(self) -> Stacks {
+        self.stacks
+    }
+
+    /// The moves currently applied to the stacks, oldest first
+    pub fn applied(&self) -> &[Entry] {
+        &self.applied
+    }
+
+    /// The number of moves currently applied to the stacks
+    pub fn position(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// The number of moves known to the journal, including the ones that can be redone
+    pub fn len(&self) -> usize {
+        self.applied.len() + self.undone.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn execute(&mut self, instruction: Move) -> anyhow::Result<Entry> {
+        let Move {
+            count,
+            from_col,
+            to_col,
+        } = instruction;
+        self.stacks.check(count, from_col, to_col)?;
+        let crates = self.stacks.popn(from_col, count)?;
+        let mut landing = crates.clone();
+        if self.crane == Crane::CrateMover9000 {
+            landing.reverse();
+        }
+        self.stacks.pushn(to_col, &landing)?;
+        Ok(Entry {
+            instruction,
+            crates,
+        })
+    }
+
+    /// Apply a new move. Any moves that were undone can no longer be redone afterwards.
+    pub fn apply(&mut self, instruction: Move) -> anyhow::Result<&Entry> {
+        let entry = self.execute(instruction)?;
+        self.undone.clear();
+        self.applied.push(entry);
+        Ok(self.applied.last().unwrap())
+    }
+
+    /// Revert the last applied move, returning it if there was one
+    pub fn undo(&mut self) -> anyhow::Result<Option<&Entry>> {
+        let entry = match self.applied.pop() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let Move {
+            count,
+            from_col,
+            to_col,
+        } = entry.instruction;
+        let mut landed = self.stacks.popn(to_col, count)?;
+        if self.crane == Crane::CrateMover9000 {
+            landed.reverse();
+        }
+        if landed != entry.crates {
+            return Err(e!(
+                "journal out of sync with stacks undoing {}",
+                entry.instruction
+            ));
+        }
+        self.stacks.pushn(from_col, &entry.crates)?;
+        self.undone.push(entry);
+        Ok(self.undone.last())
+    }
+
+    /// Re-apply the last undone move, returning it if there was one
+    pub fn redo(&mut self) -> anyhow::Result<Option<&Entry>> {
+        let instruction = match self.undone.last() {
+            Some(entry) => entry.instruction,
+            None => return Ok(None),
+        };
+        let entry = self.execute(instruction)?;
+        self.undone.pop();
+        self.applied.push(entry);
+        Ok(self.applied.last())
+    }
+
+    /// Undo or redo moves until exactly `position` moves are applied
+    pub fn seek(&mut self, position: usize) -> anyhow::Result<()> {
+        if position > self.len() {
+            return Err(e!(
+                "cannot seek to move {}, only {} moves are known",
+                position,
+                self.len()
+            ));
+        }
+        while self.position() > position {
+            self.undo()?;
+        }
+        while self.position() < position {
+            self.redo()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_move_instructions, parse_stacks};
+
+    fn example() -> (Stacks, Vec<Move>) {
+        let example = include_str!("../example.txt");
+        let mut reader = example.as_bytes();
+        let stacks = parse_stacks(&mut reader).unwrap();
+        let moves = parse_move_instructions(&mut reader).unwrap();
+        (stacks, moves)
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (stacks, moves) = example();
+        for crane in [Crane::CrateMover9000, Crane::CrateMover9001] {
+            let mut journal = Journal::new(stacks.clone(), crane);
+            let mut states = vec![stacks.clone()];
+            for m in &moves {
+                journal.apply(*m).unwrap();
+                states.push(journal.stacks().clone());
+            }
+            while journal.undo().unwrap().is_some() {
+                assert_eq!(journal.stacks(), &states[journal.position()]);
+            }
+            assert_eq!(journal.stacks(), &stacks);
+            journal.seek(3).unwrap();
+            assert_eq!(journal.stacks(), &states[3]);
+            journal.redo().unwrap();
+            assert_eq!(journal.stacks(), &states[4]);
+            assert!(journal.redo().unwrap().is_none());
+            assert!(journal.seek(5).is_err());
+        }
+    }
+
+    #[test]
+    fn test_apply_clears_redo() {
+        let (stacks, moves) = example();
+        let mut journal = Journal::new(stacks, Crane::CrateMover9001);
+        journal.apply(moves[0]).unwrap();
+        journal.apply(moves[1]).unwrap();
+        journal.undo().unwrap();
+        assert_eq!(journal.len(), 2);
+        journal.apply(moves[1]).unwrap();
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal.applied()[1].crates, vec!['Z', 'N', 'D']);
+    }
+
+    #[test]
+    fn test_invalid_move_rolls_back() {
+        let (stacks, moves) = example();
+        let mut journal = Journal::new(stacks, Crane::CrateMover9000);
+        journal.apply(moves[0]).unwrap();
+        let before = journal.stacks().clone();
+        let too_many = Move {
+            count: 4,
+            from_col: 1,
+            to_col: 2,
+        };
+        assert!(journal.apply(too_many).is_err());
+        let bad_column = Move {
+            count: 1,
+            from_col: 1,
+            to_col: 4,
+        };
+        assert!(journal.apply(bad_column).is_err());
+        assert_eq!(journal.stacks(), &before);
+        assert_eq!(journal.len(), 1);
+    }
+}
@@ -0,0 +1,384 @@
+use anyhow::anyhow as e;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+use std::io::prelude::*;
+use std::str::FromStr;
+use std::time::Duration;
+use validate::Parser;
+
+pub mod animate;
+pub mod journal;
+pub mod search;
+pub mod validate;
+
+lazy_static! {
+    pub(crate) static ref MOVE_RE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stacks {
+    pub columns: Vec<Vec<char>>,
+}
+
+impl Stacks {
+    pub fn new(cols: usize) -> Self {
+        let mut columns = Vec::with_capacity(cols);
+        for _ in 0..cols {
+            columns.push(Vec::new())
+        }
+        Self { columns }
+    }
+
+    pub fn push(&mut self, col: usize, value: char) -> anyhow::Result<()> {
+        col.checked_sub(1)
+            .and_then(|i| self.columns.get_mut(i))
+            .ok_or_else(|| e!("invalid column {}", col))?
+            .push(value);
+        Ok(())
+    }
+
+    // Move a crate off the given stack
+    pub fn pop(&mut self, col: usize) -> Option<char> {
+        self.columns.get_mut(col.checked_sub(1)?)?.pop()
+    }
+
+    /// Take the top n crates off the given stack, bottom crate first. The stack is left untouched
+    /// if it does not hold enough crates.
+    pub fn popn(&mut self, col: usize, n: usize) -> anyhow::Result<Vec<char>> {
+        let column = col
+            .checked_sub(1)
+            .and_then(|i| self.columns.get_mut(i))
+            .ok_or_else(|| e!("invalid column {}", col))?;
+        let start = column
+            .len()
+            .checked_sub(n)
+            .ok_or_else(|| e!("not enough crates in col {}", col))?;
+        Ok(column.split_off(start))
+    }
+
+    pub fn pushn(&mut self, col: usize, vals: &[char]) -> anyhow::Result<()> {
+        col.checked_sub(1)
+            .and_then(|i| self.columns.get_mut(i))
+            .ok_or_else(|| e!("invalid column {}", col))?
+            .extend_from_slice(vals);
+        Ok(())
+    }
+
+    /// Make sure a move can be carried out in full before touching any stack
+    pub fn check(&self, count: usize, from_col: usize, to_col: usize) -> anyhow::Result<()> {
+        let from = from_col
+            .checked_sub(1)
+            .and_then(|i| self.columns.get(i))
+            .ok_or_else(|| e!("invalid column {}", from_col))?;
+        to_col
+            .checked_sub(1)
+            .and_then(|i| self.columns.get(i))
+            .ok_or_else(|| e!("invalid column {}", to_col))?;
+        if from.len() < count {
+            return Err(e!("not enough crates in col {}", from_col));
+        }
+        Ok(())
+    }
+
+    // Move count crates from one stack to another, one crate at a time
+    pub fn mv(&mut self, count: usize, from_col: usize, to_col: usize) -> anyhow::Result<()> {
+        self.check(count, from_col, to_col)?;
+        let mut vals = self.popn(from_col, count)?;
+        vals.reverse();
+        self.pushn(to_col, &vals)
+    }
+
+    // Move count crates from one stack to another, all at once keeping their order
+    pub fn mvn(&mut self, count: usize, from_col: usize, to_col: usize) -> anyhow::Result<()> {
+        self.check(count, from_col, to_col)?;
+        let vals = self.popn(from_col, count)?;
+        self.pushn(to_col, &vals)
+    }
+
+    /// List the top crate in each stack if there is one
+    pub fn list_tops(&self) -> Vec<Option<char>> {
+        self.columns.iter().map(|col| col.last().copied()).collect()
+    }
+
+    /// See the top crate of the given stack number
+    pub fn top(&self, col: usize) -> Option<char> {
+        self.columns.get(col.checked_sub(1)?)?.last().copied()
+    }
+
+    /// Apply a move instruction using the given crane model
+    pub fn apply(&mut self, crane: Crane, instruction: &Move) -> anyhow::Result<()> {
+        let Move {
+            count,
+            from_col,
+            to_col,
+        } = *instruction;
+        match crane {
+            Crane::CrateMover9000 => self.mv(count, from_col, to_col),
+            Crane::CrateMover9001 => self.mvn(count, from_col, to_col),
+        }
+    }
+
+    /// The number of crates in the tallest stack
+    pub fn max_height(&self) -> usize {
+        self.columns.iter().map(Vec::len).max().unwrap_or_default()
+    }
+
+    /// Width of a single column in the drawing, not counting the separating space
+    fn cell_width(&self) -> usize {
+        self.columns.len().to_string().len() + 2
+    }
+
+    /// The drawing line by line, from the top row down to the column numbers.
+    ///
+    /// Every cell is as wide as the widest column number plus its surrounding padding so drawings
+    /// with more than 9 columns stay aligned. `highlight` gets a chance to decorate each crate,
+    /// like `[Z]` without its padding, and each column number, padding included.
+    pub fn draw(&self, highlight: Option<&dyn Fn(Part, String) -> String>) -> Vec<String> {
+        let decorate = |part, text| match highlight {
+            Some(highlight) => highlight(part, text),
+            None => text,
+        };
+        let cell_width = self.cell_width();
+        let label_width = cell_width - 2;
+        let mut lines = Vec::new();
+        for row in (0..self.max_height()).rev() {
+            let cells: Vec<String> = (1..)
+                .zip(&self.columns)
+                .map(|(col, stack)| match stack.get(row) {
+                    Some(c) => format!(
+                        "{}{}",
+                        decorate(Part::Crate { col, row }, format!("[{c}]")),
+                        " ".repeat(cell_width - 3)
+                    ),
+                    None => " ".repeat(cell_width),
+                })
+                .collect();
+            lines.push(cells.join(" "));
+        }
+        let labels: Vec<String> = (1..=self.columns.len())
+            .map(|n| decorate(Part::Label(n), format!(" {n:<label_width$} ")))
+            .collect();
+        lines.push(labels.join(" "));
+        lines
+    }
+}
+
+/// A piece of the drawing handed to the highlighter of [`Stacks::draw`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    /// The crate at `row` from the bottom of column `col`, counting columns from 1
+    Crate { col: usize, row: usize },
+    /// The number under a column
+    Label(usize),
+}
+
+/// The crane operating on the stacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crane {
+    /// Moves crates one at a time, reversing their order (part 1)
+    CrateMover9000,
+    /// Moves several crates at once, keeping their order (part 2)
+    CrateMover9001,
+}
+
+/// Renders the stacks in the same drawing format as the puzzle input, footer included, so they
+/// can be parsed back in. See [`Stacks::draw`].
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.draw(None).join("\n"))
+    }
+}
+
+impl FromStr for Crane {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches("CrateMover") {
+            "9000" => Ok(Crane::CrateMover9000),
+            "9001" => Ok(Crane::CrateMover9001),
+            _ => Err(e!("unknown crane model {}", s)),
+        }
+    }
+}
+
+impl FromStr for Stacks {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_stacks(&mut s.as_bytes())
+    }
+}
+
+pub fn parse_stacks(reader: &mut impl BufRead) -> anyhow::Result<Stacks> {
+    let mut puzzle_lines: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line = line?.trim_end().to_string();
+        if line.is_empty() {
+            break;
+        }
+        puzzle_lines.push(line)
+    }
+    let stacks = Parser::default().parse_drawing(&puzzle_lines, 1, &mut Vec::new())?;
+    Ok(stacks)
+}
+
+pub fn parse_move_instructions(reader: &mut impl BufRead) -> anyhow::Result<Vec<Move>> {
+    let mut moves = Vec::new();
+    for line in reader.lines() {
+        moves.push(Move::from_str(&line?)?);
+    }
+    Ok(moves)
+}
+
+pub fn parse_input(filename: &str) -> anyhow::Result<(Stacks, Vec<Move>)> {
+    let input = Parser::default().parse_file(filename)?;
+    let moves = input.instructions();
+    Ok((input.stacks, moves))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub count: usize,
+    pub from_col: usize,
+    pub to_col: usize,
+}
+
+impl FromStr for Move {
+    type Err = anyhow::Error;
+    fn from_str(line: &str) -> anyhow::Result<Self> {
+        let caps = MOVE_RE
+            .captures(line)
+            .ok_or_else(|| e!("line does not appear to be a move instruction: {}", &line))?;
+        let count = caps
+            .get(1)
+            .ok_or_else(|| e!("no count value in {}", &line))?
+            .as_str()
+            .parse()?;
+        let from_col = caps
+            .get(2)
+            .ok_or_else(|| e!("no from column value in {}", &line))?
+            .as_str()
+            .parse()?;
+        let to_col = caps
+            .get(3)
+            .ok_or_else(|| e!("no to column value in {}", &line))?
+            .as_str()
+            .parse()?;
+        Ok(Self {
+            count,
+            from_col,
+            to_col,
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count, self.from_col, self.to_col
+        )
+    }
+}
+
+/// Command line options shared by both parts
+pub struct Options {
+    pub filename: String,
+    pub animate: bool,
+    pub delay: Duration,
+    pub strict: bool,
+}
+
+impl Options {
+    /// Usage: `[--animate] [--delay <ms>] [--strict] [filename]`
+    pub fn from_args(args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Options {
+            filename: "input.txt".to_string(),
+            animate: false,
+            delay: Duration::from_millis(250),
+            strict: false,
+        };
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--animate" => options.animate = true,
+                "--strict" => options.strict = true,
+                "--delay" => {
+                    let ms = args.next().ok_or_else(|| e!("--delay requires a value"))?;
+                    options.delay = Duration::from_millis(ms.parse()?);
+                }
+                _ => options.filename = arg,
+            }
+        }
+        Ok(options)
+    }
+}
+
+pub fn format_tops(tops: &[Option<char>]) -> String {
+    String::from_iter(tops.iter().map(|c| c.unwrap_or('?')))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_drawing() -> &'static str {
+        let example = include_str!("../example.txt");
+        example.split("\n\n").next().unwrap()
+    }
+
+    #[test]
+    fn test_render_example() {
+        let stacks = Stacks::from_str(example_drawing()).unwrap();
+        assert_eq!(stacks.to_string(), example_drawing());
+    }
+
+    #[test]
+    fn test_round_trip_input() {
+        let (stacks, _) = parse_input("input.txt").unwrap();
+        let rendered = stacks.to_string();
+        let reparsed = Stacks::from_str(&rendered).unwrap();
+        assert_eq!(reparsed.columns, stacks.columns);
+        assert_eq!(reparsed.to_string(), rendered);
+    }
+
+    #[test]
+    fn test_top() {
+        let stacks = Stacks::from_str(example_drawing()).unwrap();
+        assert_eq!(stacks.top(0), None);
+        assert_eq!(stacks.top(1), Some('N'));
+        assert_eq!(stacks.top(3), Some('P'));
+        assert_eq!(stacks.top(4), None);
+    }
+
+    #[test]
+    fn test_round_trip_wide() {
+        let mut stacks = Stacks::new(12);
+        for (col, c) in (1..=12).zip('A'..) {
+            for _ in 0..col % 4 {
+                stacks.push(col, c).unwrap();
+            }
+        }
+        let rendered = stacks.to_string();
+        assert!(rendered.ends_with(" 9    10   11   12 "), "{}", rendered);
+        let reparsed = Stacks::from_str(&rendered).unwrap();
+        assert_eq!(reparsed.columns, stacks.columns);
+    }
+
+    #[test]
+    fn test_moves() {
+        let example = include_str!("../example.txt");
+        let mut reader = example.as_bytes();
+        let mut p1 = parse_stacks(&mut reader).unwrap();
+        let moves = parse_move_instructions(&mut reader).unwrap();
+        let mut p2 = Stacks::from_str(&p1.to_string()).unwrap();
+        for m in &moves {
+            p1.apply(Crane::CrateMover9000, m).unwrap();
+            p2.apply(Crane::CrateMover9001, m).unwrap();
+        }
+        assert_eq!(format_tops(&p1.list_tops()), "CMZ");
+        assert_eq!(format_tops(&p2.list_tops()), "MCD");
+    }
+}
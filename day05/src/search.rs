@@ -0,0 +1,194 @@
+use crate::{Crane, Move, Stacks};
+use anyhow::anyhow as e;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem::size_of;
+
+fn sorted_crates(stacks: &Stacks) -> Vec<char> {
+    let mut crates: Vec<char> = stacks.columns.iter().flatten().copied().collect();
+    crates.sort_unstable();
+    crates
+}
+
+/// Every move that can be made from the given state
+fn candidates(stacks: &Stacks) -> impl Iterator<Item = Move> + '_ {
+    let cols = stacks.columns.len();
+    (1..=cols).flat_map(move |from_col| {
+        let available = stacks.columns[from_col - 1].len();
+        (1..=cols)
+            .filter(move |&to_col| to_col != from_col)
+            .flat_map(move |to_col| {
+                (1..=available).map(move |count| Move {
+                    count,
+                    from_col,
+                    to_col,
+                })
+            })
+    })
+}
+
+/// Bytes the allocator is assumed to need for its own bookkeeping with every allocation
+const ALLOCATION_OVERHEAD: usize = 16;
+
+// `BuildHasher::hash_one` needs Rust 1.71
+#[allow(clippy::manual_hash_one)]
+fn hash_of(hasher: &RandomState, stacks: &Stacks) -> u64 {
+    let mut state = hasher.build_hasher();
+    stacks.hash(&mut state);
+    state.finish()
+}
+
+/// Rough number of bytes the search needs for every state of stacks like `stacks`.
+///
+/// Counts the columns and crates of the state itself, allowing for columns that grew and the
+/// allocator's bookkeeping, along with the state's parent and its slot in the lookup table.
+pub fn bytes_per_state(stacks: &Stacks) -> usize {
+    let crates: usize = stacks.columns.iter().map(Vec::len).sum();
+    let columns = stacks.columns.len();
+    let state = size_of::<Stacks>()
+        + columns * (size_of::<Vec<char>>() + ALLOCATION_OVERHEAD)
+        + 2 * crates * size_of::<char>();
+    let parent = size_of::<Option<(usize, Move)>>();
+    // a hash, a bucket of indices and room for the table's load factor
+    let lookup = 2 * (size_of::<u64>() + size_of::<Vec<usize>>() + size_of::<usize>());
+    state + parent + lookup
+}
+
+/// The number of states of stacks like `stacks` that fit in `bytes` of memory
+pub fn states_within(stacks: &Stacks, bytes: usize) -> usize {
+    bytes / bytes_per_state(stacks)
+}
+
+/// Find a shortest list of moves turning `start` into `target` using the given crane.
+///
+/// This is a breadth first search over stack states so it gives up with an error once more than
+/// `max_states` states have been seen, see [`states_within`] for a limit that fits in memory.
+/// `Ok(None)` means the whole reachable state space was searched without finding the target.
+pub fn shortest_moves(
+    start: &Stacks,
+    target: &Stacks,
+    crane: Crane,
+    max_states: usize,
+) -> anyhow::Result<Option<Vec<Move>>> {
+    if start.columns.len() != target.columns.len() {
+        return Err(e!(
+            "start has {} columns but target has {}",
+            start.columns.len(),
+            target.columns.len()
+        ));
+    }
+    if sorted_crates(start) != sorted_crates(target) {
+        return Err(e!("start and target do not hold the same crates"));
+    }
+    // every state is kept once, in the order it was first reached, which is also the order a
+    // breadth first search visits them in, along with the state and move it was reached from
+    let mut states = vec![start.clone()];
+    let mut parents: Vec<Option<(usize, Move)>> = vec![None];
+    // the indices of the states with each hash
+    let hasher = RandomState::new();
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    seen.insert(hash_of(&hasher, start), vec![0]);
+    let mut next_state = 0;
+    while next_state < states.len() {
+        let current = next_state;
+        next_state += 1;
+        if &states[current] == target {
+            let mut path = Vec::new();
+            let mut ptr = current;
+            while let Some((parent, instruction)) = parents[ptr] {
+                path.push(instruction);
+                ptr = parent;
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        let instructions: Vec<Move> = candidates(&states[current]).collect();
+        for instruction in instructions {
+            let mut next = states[current].clone();
+            next.apply(crane, &instruction)?;
+            let bucket = seen.entry(hash_of(&hasher, &next)).or_default();
+            if bucket.iter().any(|&i| states[i] == next) {
+                continue;
+            }
+            if states.len() >= max_states {
+                return Err(e!("gave up after exploring {} states", max_states));
+            }
+            bucket.push(states.len());
+            states.push(next);
+            parents.push(Some((current, instruction)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_move_instructions, parse_stacks};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_shortest_moves() {
+        let example = include_str!("../example.txt");
+        let mut reader = example.as_bytes();
+        let start = parse_stacks(&mut reader).unwrap();
+        let moves = parse_move_instructions(&mut reader).unwrap();
+        for crane in [Crane::CrateMover9000, Crane::CrateMover9001] {
+            let mut target = start.clone();
+            for m in &moves {
+                target.apply(crane, m).unwrap();
+            }
+            let solution = shortest_moves(&start, &target, crane, 1_000_000)
+                .unwrap()
+                .expect("target should be reachable");
+            assert!(solution.len() <= moves.len());
+            let mut replay = start.clone();
+            for m in &solution {
+                let text = m.to_string();
+                replay
+                    .apply(crane, &Move::from_str(&text).unwrap())
+                    .unwrap();
+            }
+            assert_eq!(replay, target);
+        }
+    }
+
+    #[test]
+    fn test_trivial_and_impossible() {
+        let start = Stacks::from_str("[A]    \n 1   2 ").unwrap();
+        let solution = shortest_moves(&start, &start, Crane::CrateMover9000, 10).unwrap();
+        assert_eq!(solution, Some(Vec::new()));
+
+        let swapped = Stacks::from_str("[B]    \n 1   2 ").unwrap();
+        assert!(shortest_moves(&start, &swapped, Crane::CrateMover9000, 10).is_err());
+
+        // with a single stack there is nowhere to move crates to
+        let start = Stacks::from_str("[A]\n[B]\n 1 ").unwrap();
+        let target = Stacks::from_str("[B]\n[A]\n 1 ").unwrap();
+        assert_eq!(
+            shortest_moves(&start, &target, Crane::CrateMover9001, 10).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gives_up() {
+        let start = Stacks::from_str(
+            "\
+[A] [B] [C] [D] [E] [F] [G] [H] [I]
+[J] [K] [L] [M] [N] [O] [P] [Q] [R]
+ 1   2   3   4   5   6   7   8   9 ",
+        )
+        .unwrap();
+        let mut target = start.clone();
+        target.columns.reverse();
+        let max_states = states_within(&start, 1 << 20);
+        assert!(max_states > 1000);
+        let err = shortest_moves(&start, &target, Crane::CrateMover9001, max_states).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("gave up after exploring {} states", max_states)
+        );
+    }
+}
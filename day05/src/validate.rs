@@ -0,0 +1,408 @@
+use crate::{Crane, Move, Stacks, MOVE_RE};
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+
+lazy_static! {
+    static ref COLUMN_RE: Regex = Regex::new(r"\d+").unwrap();
+}
+
+/// A problem found in the input. Lines and columns are 1-based like in an editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// A move instruction along with where it was found in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMove {
+    pub line: usize,
+    /// The text columns of the count, from and to numbers
+    pub columns: [usize; 3],
+    pub instruction: Move,
+}
+
+/// A fully parsed puzzle input
+#[derive(Debug)]
+pub struct Input {
+    pub stacks: Stacks,
+    pub moves: Vec<SourceMove>,
+    /// Ambiguities that were tolerated because the parser was not strict
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Input {
+    pub fn instructions(&self) -> Vec<Move> {
+        self.moves.iter().map(|m| m.instruction).collect()
+    }
+
+    /// Run the moves on a copy of the stacks, reporting the first one that takes more crates
+    /// than its source stack holds
+    pub fn check(&self, crane: Crane) -> Result<(), Diagnostic> {
+        let mut stacks = self.stacks.clone();
+        for m in &self.moves {
+            let Move {
+                count, from_col, ..
+            } = m.instruction;
+            let available = stacks.columns[from_col - 1].len();
+            if available < count {
+                let message = if available == 0 {
+                    format!("cannot move {} from stack {}, it is empty", count, from_col)
+                } else {
+                    format!(
+                        "cannot move {} from stack {}, it only holds {}",
+                        count, from_col, available
+                    )
+                };
+                return Err(Diagnostic::new(m.line, m.columns[1], message));
+            }
+            stacks
+                .apply(crane, &m.instruction)
+                .map_err(|err| Diagnostic::new(m.line, 1, err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a byte offset into a 1-based character column
+fn char_column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+/// Parses the puzzle input keeping track of where every crate and move came from.
+///
+/// Text that cannot be understood at all, move instructions naming columns that do not exist
+/// and drawings without column numbers are always errors. Anything the original parser quietly
+/// guessed its way around (crates that do not line up with a column number, stray characters,
+/// floating crates, extra text around a move) is collected as a warning, or refused outright
+/// when `strict` is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Parser {
+    pub strict: bool,
+}
+
+impl Parser {
+    pub fn strict() -> Self {
+        Self { strict: true }
+    }
+
+    /// Record a problem that is only fatal in strict mode
+    fn ambiguity(
+        &self,
+        warnings: &mut Vec<Diagnostic>,
+        diagnostic: Diagnostic,
+    ) -> Result<(), Diagnostic> {
+        if self.strict {
+            return Err(diagnostic);
+        }
+        warnings.push(diagnostic);
+        Ok(())
+    }
+
+    pub fn parse_file(&self, filename: &str) -> anyhow::Result<Input> {
+        let text = std::fs::read_to_string(filename)?;
+        let input = self
+            .parse(&text)
+            .with_context(|| format!("failed to parse {}", filename))?;
+        Ok(input)
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Input, Diagnostic> {
+        let lines: Vec<&str> = input.lines().collect();
+        let split = lines
+            .iter()
+            .position(|line| line.trim().is_empty())
+            .unwrap_or(lines.len());
+        let mut warnings = Vec::new();
+        let stacks = self.parse_drawing(&lines[..split], 1, &mut warnings)?;
+        let mut moves = Vec::new();
+        for (i, line) in lines.iter().enumerate().skip(split + 1) {
+            let parsed = self.parse_move(line, i + 1, stacks.columns.len(), &mut warnings)?;
+            moves.extend(parsed);
+        }
+        Ok(Input {
+            stacks,
+            moves,
+            warnings,
+        })
+    }
+
+    /// Parse the stack drawing, footer included. `first_line` is the line number of the first
+    /// line of the drawing.
+    pub fn parse_drawing(
+        &self,
+        lines: &[impl AsRef<str>],
+        first_line: usize,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Stacks, Diagnostic> {
+        let (footer, rows) = lines
+            .split_last()
+            .ok_or_else(|| Diagnostic::new(first_line, 1, "no puzzle lines detected"))?;
+        let footer = footer.as_ref();
+        let footer_line = first_line + rows.len();
+        let positions = self.parse_footer(footer, footer_line, warnings)?;
+        let mut stacks = Stacks::new(positions.len());
+        for (offset, row) in rows.iter().enumerate().rev() {
+            let line = first_line + offset;
+            let height = rows.len() - 1 - offset;
+            let chars: Vec<char> = row.as_ref().chars().collect();
+            // (column index, crate, text column) for every crate on this row
+            let mut found = Vec::new();
+            let mut i = 0;
+            while i < chars.len() {
+                let c = chars[i];
+                if c.is_whitespace() {
+                    i += 1;
+                    continue;
+                }
+                let boxed = c == '['
+                    && chars.get(i + 1).filter(|c| c.is_alphabetic()).is_some()
+                    && chars.get(i + 2) == Some(&']');
+                if boxed {
+                    let name = chars[i + 1];
+                    match positions.iter().position(|&p| p == i + 1) {
+                        Some(col) => found.push((col, name, i + 1)),
+                        None => {
+                            let message =
+                                format!("crate [{}] is not aligned with a column number", name);
+                            self.ambiguity(warnings, Diagnostic::new(line, i + 1, message))?
+                        }
+                    }
+                    i += 3;
+                    continue;
+                }
+                match positions.iter().position(|&p| p == i) {
+                    Some(col) if c.is_alphabetic() => {
+                        let message = format!("crate {} is missing its brackets", c);
+                        self.ambiguity(warnings, Diagnostic::new(line, i + 1, message))?;
+                        found.push((col, c, i + 1));
+                    }
+                    _ => {
+                        let message = format!("unexpected character '{}'", c);
+                        self.ambiguity(warnings, Diagnostic::new(line, i + 1, message))?;
+                    }
+                }
+                i += 1;
+            }
+            for (col, c, column) in found {
+                if stacks.columns[col].len() < height {
+                    let message = format!("crate {} is floating above an empty space", c);
+                    self.ambiguity(warnings, Diagnostic::new(line, column, message))?;
+                }
+                stacks.columns[col].push(c);
+            }
+        }
+        Ok(stacks)
+    }
+
+    /// Returns the character index each column number starts at
+    fn parse_footer(
+        &self,
+        footer: &str,
+        line: usize,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Vec<usize>, Diagnostic> {
+        if let Some((i, c)) = footer
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_digit() && !c.is_whitespace())
+        {
+            let message = format!("unexpected character '{}' in the column numbers", c);
+            self.ambiguity(warnings, Diagnostic::new(line, i + 1, message))?;
+        }
+        let mut positions = Vec::new();
+        for (m, expected) in COLUMN_RE.find_iter(footer).zip(1..) {
+            let column = char_column(footer, m.start());
+            if m.as_str().parse::<usize>().ok() != Some(expected) {
+                let message = format!(
+                    "expected column number {} but found {}",
+                    expected,
+                    m.as_str()
+                );
+                self.ambiguity(warnings, Diagnostic::new(line, column, message))?;
+            }
+            positions.push(column - 1);
+        }
+        if positions.is_empty() {
+            return Err(Diagnostic::new(
+                line,
+                1,
+                "no column numbers below the stacks",
+            ));
+        }
+        Ok(positions)
+    }
+
+    fn parse_move(
+        &self,
+        text: &str,
+        line: usize,
+        columns: usize,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Option<SourceMove>, Diagnostic> {
+        if text.trim().is_empty() {
+            self.ambiguity(
+                warnings,
+                Diagnostic::new(line, 1, "blank line between moves"),
+            )?;
+            return Ok(None);
+        }
+        let caps = MOVE_RE.captures(text).ok_or_else(|| {
+            let message = format!("line does not appear to be a move instruction: {}", text);
+            Diagnostic::new(line, 1, message)
+        })?;
+        let whole = caps.get(0).unwrap();
+        // extra text is reported where it starts, skipping any whitespace around the move
+        let before = &text[..whole.start()];
+        if !before.trim().is_empty() {
+            let column = char_column(text, before.len() - before.trim_start().len());
+            self.ambiguity(warnings, Diagnostic::new(line, column, "text before move"))?;
+        }
+        let after = &text[whole.end()..];
+        if !after.trim().is_empty() {
+            let start = whole.end() + after.len() - after.trim_start().len();
+            let column = char_column(text, start);
+            self.ambiguity(warnings, Diagnostic::new(line, column, "text after move"))?;
+        }
+        let mut values = [0; 3];
+        let mut at = [0; 3];
+        for i in 0..3 {
+            let m = caps.get(i + 1).unwrap();
+            at[i] = char_column(text, m.start());
+            values[i] = m.as_str().parse().map_err(|_| {
+                Diagnostic::new(line, at[i], format!("{} is too large", m.as_str()))
+            })?;
+        }
+        let [count, from_col, to_col] = values;
+        for i in 1..3 {
+            if values[i] == 0 || values[i] > columns {
+                let message = format!(
+                    "unknown column {}, the drawing has {} columns",
+                    values[i], columns
+                );
+                return Err(Diagnostic::new(line, at[i], message));
+            }
+        }
+        if count == 0 {
+            self.ambiguity(
+                warnings,
+                Diagnostic::new(line, at[0], "move of zero crates"),
+            )?;
+        }
+        Ok(Some(SourceMove {
+            line,
+            columns: at,
+            instruction: Move {
+                count,
+                from_col,
+                to_col,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_example_is_strict() {
+        for text in [include_str!("../example.txt"), include_str!("../input.txt")] {
+            let input = Parser::strict().parse(text).unwrap();
+            assert!(input.warnings.is_empty());
+            input.check(Crane::CrateMover9000).unwrap();
+            input.check(Crane::CrateMover9001).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_misaligned_crate() {
+        let text = "    [D]    \n[N]  [C]   \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n";
+        let input = Parser::default().parse(text).unwrap();
+        assert_eq!(
+            input.warnings,
+            vec![
+                Diagnostic::new(2, 6, "crate [C] is not aligned with a column number"),
+                Diagnostic::new(1, 5, "crate D is floating above an empty space"),
+            ]
+        );
+        assert_eq!(input.stacks.columns[1], vec!['M', 'D']);
+        let err = Parser::strict().parse(text).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
+    }
+
+    #[test]
+    fn test_bad_moves() {
+        let drawing = "[A]    \n 1   2 \n\n";
+        let err = Parser::default()
+            .parse(&format!(
+                "{drawing}move 1 from 1 to 2\nmove one from 1 to 2\n"
+            ))
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (5, 1));
+
+        let err = Parser::default()
+            .parse(&format!("{drawing}move 1 from 1 to 3\n"))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Diagnostic::new(4, 18, "unknown column 3, the drawing has 2 columns")
+        );
+
+        let input = Parser::default()
+            .parse(&format!(
+                "{drawing}move 1 from 1 to 2\nmove 1 from 1 to 2\n"
+            ))
+            .unwrap();
+        let err = input.check(Crane::CrateMover9001).unwrap_err();
+        assert_eq!(
+            err,
+            Diagnostic::new(5, 13, "cannot move 1 from stack 1, it is empty")
+        );
+
+        let text = format!("{drawing}move 1 from 1 to 2 please\n");
+        assert_eq!(Parser::default().parse(&text).unwrap().warnings.len(), 1);
+        let err = Parser::strict().parse(&text).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 20));
+
+        for (line, column, message) in [
+            ("move 1 from 1 to 2   please", 22, "text after move"),
+            ("move 1 from 1 to 2x", 19, "text after move"),
+            ("now move 1 from 1 to 2", 1, "text before move"),
+            ("  \tthen move 1 from 1 to 2", 4, "text before move"),
+        ] {
+            let err = Parser::strict()
+                .parse(&format!("{drawing}{line}\n"))
+                .unwrap_err();
+            assert_eq!(err, Diagnostic::new(4, column, message), "{:?}", line);
+        }
+        // whitespace alone around a move is not worth a warning
+        let text = format!("{drawing}  move 1 from 1 to 2  \n");
+        assert!(Parser::strict().parse(&text).is_ok());
+    }
+}
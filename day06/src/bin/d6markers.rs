@@ -0,0 +1,57 @@
+//! List every window of distinct characters in a datastream.
+//!
+//! Usage: `d6markers [--size <n>]... [--first <n>] [--report start|end|processed] [filename]`
+//!
+//! Prints one `<size> <offset>` line per window found, in stream order. Defaults to the
+//! start-of-packet and start-of-message sizes, reporting the characters processed.
+use anyhow::anyhow as e;
+use day06::{scan_each, Detector, FirstLine, Marker, Report};
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+
+fn main() -> anyhow::Result<()> {
+    let mut filename = "input.txt".to_string();
+    let mut sizes = Vec::new();
+    let mut limit = None;
+    let mut report = Report::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let size: usize = args
+                    .next()
+                    .ok_or_else(|| e!("--size requires a value"))?
+                    .parse()?;
+                if size == 0 {
+                    return Err(e!("--size must be at least 1"));
+                }
+                sizes.push(size);
+            }
+            "--first" => {
+                let n = args.next().ok_or_else(|| e!("--first requires a value"))?;
+                limit = Some(n.parse()?);
+            }
+            "--report" => {
+                let r = args.next().ok_or_else(|| e!("--report requires a value"))?;
+                report = r.parse()?;
+            }
+            _ => filename = arg,
+        }
+    }
+    if sizes.is_empty() {
+        sizes = vec![Marker::StartOfPacket.size(), Marker::StartOfMessage.size()];
+    }
+    let mut detector = Detector::new(&sizes, limit);
+    let mut out = BufWriter::new(std::io::stdout());
+    let mut result = Ok(());
+    let reader = FirstLine::new(BufReader::new(File::open(&filename)?));
+    scan_each(reader, &mut detector, |event| {
+        if result.is_ok() {
+            result = writeln!(out, "{} {}", event.size, event.report(report));
+        }
+    })?;
+    result?;
+    out.flush()?;
+    Ok(())
+}
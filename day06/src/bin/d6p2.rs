@@ -1,30 +1,19 @@
-use std::collections::HashSet;
-
-fn find_unique_window(size: usize, data: &[impl PartialEq]) -> Option<usize> {
-    let mut ptr = 0;
-    for (i, c) in data.iter().enumerate() {
-        if let Some(last_index) = data[ptr..i].iter().position(|w| w == c) {
-            ptr += last_index + 1;
-        } else if i - ptr + 1 == size {
-            return Some(ptr);
-        }
-    }
-    None
-}
+use day06::{scan, FirstLine, Marker, Options};
+use std::fs::File;
+use std::io::BufReader;
 
 fn main() -> anyhow::Result<()> {
-    let filename = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "input.txt".to_string());
-    let data: Vec<char> = String::from_utf8(std::fs::read(&filename)?)?
-        .chars()
-        .collect();
-    match find_unique_window(4, &data) {
-        Some(packet_start) => println!("Start of packet: {}", packet_start),
+    let options = Options::from_args(std::env::args().skip(1))?;
+    let reader = FirstLine::new(BufReader::new(File::open(&options.filename)?));
+    let markers = [Marker::StartOfPacket, Marker::StartOfMessage];
+    let events = scan(reader, &markers)?;
+    let find = |marker| events.iter().find(|event| event.is(marker));
+    match find(Marker::StartOfPacket) {
+        Some(packet) => println!("Start of packet: {}", packet.report(options.report)),
         None => println!("Could not find packet start"),
     }
-    match find_unique_window(14, &data) {
-        Some(message_start) => println!("Start of message: {}", message_start),
+    match find(Marker::StartOfMessage) {
+        Some(message) => println!("Start of message: {}", message.report(options.report)),
         None => println!("Could not find message start"),
     }
     Ok(())
@@ -0,0 +1,552 @@
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::prelude::*;
+use std::str::FromStr;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The markers used by the elves' communication protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    StartOfPacket,
+    StartOfMessage,
+}
+
+impl Marker {
+    /// The number of distinct characters that make up the marker
+    pub fn size(&self) -> usize {
+        match self {
+            Marker::StartOfPacket => 4,
+            Marker::StartOfMessage => 14,
+        }
+    }
+}
+
+/// A window of distinct characters found in the datastream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// The number of characters in the window
+    pub size: usize,
+    /// Absolute offset of the first character of the window
+    pub start: usize,
+}
+
+impl Event {
+    pub fn is(&self, marker: Marker) -> bool {
+        self.size == marker.size()
+    }
+
+    /// Offset of the last character of the window
+    pub fn end(&self) -> usize {
+        self.start + self.size - 1
+    }
+
+    /// The number of characters processed by the time the window is complete
+    pub fn processed(&self) -> usize {
+        self.start + self.size
+    }
+
+    pub fn report(&self, report: Report) -> usize {
+        match report {
+            Report::Start => self.start,
+            Report::End => self.end(),
+            Report::Processed => self.processed(),
+        }
+    }
+}
+
+/// Which position to report for a window occupying offsets `start..start + size` of the stream.
+///
+/// All offsets are 0-based. For `mjqjpqmgbljsphdztnvjfqwrcgsmlb` the first start-of-packet
+/// marker is `jpqm`, which has a start of 3, an end of 6, and is complete after 7 characters have
+/// been processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Report {
+    /// The offset of the first character of the window
+    Start,
+    /// The offset of the last character of the window
+    End,
+    /// The number of characters processed once the window is complete, which is the puzzle answer
+    #[default]
+    Processed,
+}
+
+impl FromStr for Report {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Report::Start),
+            "end" => Ok(Report::End),
+            "processed" => Ok(Report::Processed),
+            _ => Err(anyhow!(
+                "unknown report {}, expected start, end or processed",
+                s
+            )),
+        }
+    }
+}
+
+/// Command line options shared by both parts
+pub struct Options {
+    pub filename: String,
+    pub report: Report,
+}
+
+impl Options {
+    /// Usage: `[--report start|end|processed] [filename]`
+    pub fn from_args(args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Options {
+            filename: "input.txt".to_string(),
+            report: Report::default(),
+        };
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--report" => {
+                    let report = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--report requires a value"))?;
+                    options.report = report.parse()?;
+                }
+                _ => options.filename = arg,
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Reads from a buffered reader up to the first line break.
+///
+/// The puzzle input is a single line of text; its trailing line break is not part of the
+/// datastream and must not be able to complete a marker.
+pub struct FirstLine<R> {
+    inner: R,
+    done: bool,
+}
+
+impl<R: BufRead> FirstLine<R> {
+    pub fn new(inner: R) -> Self {
+        FirstLine { inner, done: false }
+    }
+}
+
+impl<R: BufRead> Read for FirstLine<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+        let available = self.inner.fill_buf()?;
+        let line_end = available.iter().position(|&b| b == b'\n' || b == b'\r');
+        let n = line_end.unwrap_or(available.len()).min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.inner.consume(n);
+        if line_end == Some(n) {
+            self.done = true;
+        }
+        Ok(n)
+    }
+}
+
+/// Remembers the last position each symbol was seen at
+pub trait Alphabet<T> {
+    /// Record `position` as the latest sighting of `symbol`, returning the previous one
+    fn replace(&mut self, symbol: T, position: usize) -> Option<usize>;
+}
+
+/// A lookup table covering every possible byte
+pub struct Bytes([Option<usize>; 256]);
+
+impl Default for Bytes {
+    fn default() -> Self {
+        Bytes([None; 256])
+    }
+}
+
+impl Alphabet<u8> for Bytes {
+    fn replace(&mut self, symbol: u8, position: usize) -> Option<usize> {
+        self.0[symbol as usize].replace(position)
+    }
+}
+
+/// Any hashable symbol, for alphabets too large or sparse for a lookup table
+pub struct Symbols<T>(HashMap<T, usize>);
+
+impl<T> Default for Symbols<T> {
+    fn default() -> Self {
+        Symbols(HashMap::new())
+    }
+}
+
+impl<T: Hash + Eq> Alphabet<T> for Symbols<T> {
+    fn replace(&mut self, symbol: T, position: usize) -> Option<usize> {
+        self.0.insert(symbol, position)
+    }
+}
+
+/// Slides a window of `size` symbols over a sequence, one symbol at a time.
+///
+/// Rather than holding on to the window itself it tracks where each symbol was last seen and
+/// where the current run without repeats started, so every symbol is handled in constant time
+/// however large the window is.
+pub struct UniqueWindow<A> {
+    size: usize,
+    alphabet: A,
+    run_start: usize,
+    position: usize,
+}
+
+impl<A> UniqueWindow<A> {
+    pub fn new(size: usize, alphabet: A) -> Self {
+        assert!(size > 0, "window size must be at least 1");
+        UniqueWindow {
+            size,
+            alphabet,
+            run_start: 0,
+            position: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of symbols pushed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Slide the window forward by one symbol. Returns true if the window is now full and every
+    /// symbol in it is different.
+    pub fn push<T>(&mut self, symbol: T) -> bool
+    where
+        A: Alphabet<T>,
+    {
+        if let Some(last) = self.alphabet.replace(symbol, self.position) {
+            if last >= self.run_start {
+                self.run_start = last + 1;
+            }
+        }
+        self.position += 1;
+        self.position - self.run_start >= self.size
+    }
+}
+
+/// Find the index of the first symbol of the first window of `size` symbols that are all
+/// different
+pub fn find_unique_window<T: Hash + Eq>(
+    data: impl IntoIterator<Item = T>,
+    size: usize,
+) -> Option<usize> {
+    find_unique_window_in(data, size, Symbols::default())
+}
+
+/// [`find_unique_window`] using the given alphabet to keep track of symbols
+pub fn find_unique_window_in<T, A: Alphabet<T>>(
+    data: impl IntoIterator<Item = T>,
+    size: usize,
+    alphabet: A,
+) -> Option<usize> {
+    let mut window = UniqueWindow::new(size, alphabet);
+    for symbol in data {
+        if window.push(symbol) {
+            return Some(window.position() - size);
+        }
+    }
+    None
+}
+
+/// Finds windows of distinct characters of several sizes in a datastream that arrives in chunks.
+///
+/// Only the current state of each window is kept so memory use does not depend on the length of
+/// the stream. Chunks can come from anywhere, including an async reader, by handing them to
+/// [`Detector::feed`] as they arrive.
+pub struct Detector {
+    offset: usize,
+    limit: Option<usize>,
+    searches: Vec<(UniqueWindow<Bytes>, usize)>,
+}
+
+impl Detector {
+    /// Look for every window of each of the given sizes, stopping after `limit` windows of a size
+    /// have been found
+    pub fn new(sizes: &[usize], limit: Option<usize>) -> Self {
+        Detector {
+            offset: 0,
+            limit,
+            searches: sizes
+                .iter()
+                .map(|&size| (UniqueWindow::new(size, Bytes::default()), 0))
+                .collect(),
+        }
+    }
+
+    /// Look for the first occurrence of each marker
+    pub fn first(markers: &[Marker]) -> Self {
+        let sizes: Vec<usize> = markers.iter().map(Marker::size).collect();
+        Self::new(&sizes, Some(1))
+    }
+
+    /// Process the next chunk of the stream, calling `f` with every window completed in it in
+    /// stream order
+    pub fn feed_each(&mut self, chunk: &[u8], mut f: impl FnMut(Event)) {
+        for &byte in chunk {
+            if self.done() {
+                break;
+            }
+            for (window, found) in self.searches.iter_mut() {
+                let wanted = !matches!(self.limit, Some(limit) if *found >= limit);
+                if window.push(byte) && wanted {
+                    *found += 1;
+                    f(Event {
+                        size: window.size(),
+                        start: self.offset + 1 - window.size(),
+                    });
+                }
+            }
+            self.offset += 1;
+        }
+    }
+
+    /// Process the next chunk of the stream returning the windows completed in it
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.feed_each(chunk, |event| events.push(event));
+        events
+    }
+
+    /// True once the limit has been reached for every size
+    pub fn done(&self) -> bool {
+        match self.limit {
+            Some(limit) => self.searches.iter().all(|(_, found)| *found >= limit),
+            None => false,
+        }
+    }
+
+    /// The number of bytes processed so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Read the stream chunk by chunk, calling `f` with every window found, until the detector is
+/// done or the stream ends
+pub fn scan_each(
+    mut reader: impl Read,
+    detector: &mut Detector,
+    mut f: impl FnMut(Event),
+) -> std::io::Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    while !detector.done() {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        detector.feed_each(&buffer[..n], &mut f);
+    }
+    Ok(())
+}
+
+/// Find the first occurrence of each marker in the stream
+pub fn scan(reader: impl Read, markers: &[Marker]) -> std::io::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    scan_each(reader, &mut Detector::first(markers), |event| {
+        events.push(event)
+    })?;
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLES: [(&str, usize, usize); 5] = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    #[test]
+    fn test_example_files() {
+        let examples = [
+            include_str!("../example.txt"),
+            include_str!("../example1.txt"),
+        ];
+        let lines: Vec<&str> = examples.iter().flat_map(|e| e.lines()).collect();
+        assert_eq!(lines.len(), 6);
+        for line in lines {
+            let (_, packet, message) = EXAMPLES
+                .iter()
+                .find(|(data, _, _)| *data == line)
+                .expect("example missing from the README answers");
+            let markers = [Marker::StartOfPacket, Marker::StartOfMessage];
+            let events = scan(FirstLine::new(line.as_bytes()), &markers).unwrap();
+            let reported = |report| -> Vec<usize> {
+                events.iter().map(|event| event.report(report)).collect()
+            };
+            assert_eq!(reported(Report::Processed), vec![*packet, *message]);
+            assert_eq!(reported(Report::End), vec![packet - 1, message - 1]);
+            assert_eq!(reported(Report::Start), vec![packet - 4, message - 14]);
+        }
+    }
+
+    #[test]
+    fn test_marker_at_end_of_input() {
+        let markers = [Marker::StartOfPacket];
+        let events = scan(FirstLine::new(&b"aaaabcd\n"[..]), &markers).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].report(Report::Processed), 7);
+        assert_eq!(events[0].report(Report::End), 6);
+        assert_eq!(events[0].report(Report::Start), 3);
+        assert_eq!(find_unique_window("aaaabcd".chars(), 4), Some(3));
+
+        // the line break does not count as part of the datastream
+        let events = scan(FirstLine::new(&b"abca\r\nbcd"[..]), &markers).unwrap();
+        assert!(events.is_empty());
+        assert_eq!(scan(&b"abca\n"[..], &markers).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_examples() {
+        let markers = [Marker::StartOfPacket, Marker::StartOfMessage];
+        for (data, packet_end, message_end) in EXAMPLES {
+            let expected = vec![
+                Event {
+                    size: 4,
+                    start: packet_end - 4,
+                },
+                Event {
+                    size: 14,
+                    start: message_end - 14,
+                },
+            ];
+            assert_eq!(
+                scan(data.as_bytes(), &markers).unwrap(),
+                expected,
+                "{}",
+                data
+            );
+            for chunk_size in [1, 3, 5] {
+                let mut detector = Detector::first(&markers);
+                let events: Vec<Event> = data
+                    .as_bytes()
+                    .chunks(chunk_size)
+                    .flat_map(|chunk| detector.feed(chunk))
+                    .collect();
+                assert_eq!(events, expected, "{} in chunks of {}", data, chunk_size);
+            }
+        }
+    }
+
+    /// Pseudo random symbols from a small alphabet so repeats are common
+    fn noise(len: usize, alphabet: u64) -> Vec<u8> {
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                b'a' + ((state >> 33) % alphabet) as u8
+            })
+            .collect()
+    }
+
+    fn naive(data: &[u8], size: usize) -> Option<usize> {
+        data.windows(size).position(|window| {
+            let uniq: std::collections::HashSet<&u8> = window.iter().collect();
+            uniq.len() == size
+        })
+    }
+
+    #[test]
+    fn test_find_unique_window() {
+        for (data, packet_end, message_end) in EXAMPLES {
+            let chars: Vec<char> = data.chars().collect();
+            assert_eq!(find_unique_window(chars, 4), Some(packet_end - 4));
+            let bytes = data.bytes();
+            assert_eq!(
+                find_unique_window_in(bytes, 14, Bytes::default()),
+                Some(message_end - 14)
+            );
+        }
+        let words = "the cat sat on the mat".split(' ');
+        assert_eq!(find_unique_window(words.clone(), 4), Some(0));
+        assert_eq!(find_unique_window(words, 5), Some(1));
+        assert_eq!(find_unique_window("abab".chars(), 3), None);
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for alphabet in [3, 8, 20] {
+            let data = noise(2000, alphabet);
+            for size in 1..=20 {
+                assert_eq!(
+                    find_unique_window_in(data.iter().copied(), size, Bytes::default()),
+                    naive(&data, size),
+                    "window of {} over {} symbols",
+                    size,
+                    alphabet
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_windows() {
+        let data = noise(3000, 12);
+        let sizes = [1, 4, 7, 10];
+        let mut detector = Detector::new(&sizes, None);
+        let events: Vec<Event> = data.chunks(7).flat_map(|c| detector.feed(c)).collect();
+        for size in sizes {
+            let expected: Vec<usize> = data
+                .windows(size)
+                .enumerate()
+                .filter(|(_, window)| naive(window, size) == Some(0))
+                .map(|(start, _)| start)
+                .collect();
+            let found: Vec<usize> = events
+                .iter()
+                .filter(|event| event.size == size)
+                .map(|event| event.start)
+                .collect();
+            assert!(!found.is_empty());
+            assert_eq!(found, expected, "windows of {}", size);
+        }
+        let ends: Vec<usize> = events.iter().map(|e| e.start + e.size).collect();
+        assert!(ends.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut detector = Detector::new(&[4, 14], Some(3));
+        let events = detector.feed(EXAMPLES[0].0.as_bytes());
+        let starts: Vec<(usize, usize)> = events.iter().map(|e| (e.size, e.start)).collect();
+        assert_eq!(
+            starts,
+            vec![(4, 3), (4, 4), (4, 5), (14, 5), (14, 11), (14, 12)]
+        );
+        assert!(detector.done());
+        assert!(detector.offset() < EXAMPLES[0].0.len());
+    }
+
+    #[test]
+    fn test_long_stream() {
+        let padding = 10 * CHUNK_SIZE as u64 + 7;
+        let reader = std::io::repeat(b'a')
+            .take(padding)
+            .chain(&b"bcdefghijklmnopq"[..]);
+        let events = scan(reader, &[Marker::StartOfMessage]).unwrap();
+        assert_eq!(
+            events,
+            vec![Event {
+                size: 14,
+                start: padding as usize - 1,
+            }]
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Convert between terminal transcripts and JSON snapshots of the filesystem.
+//!
+//! Usage: `d7convert [--from transcript|json] [--to transcript|json|tree] [filename]`
+use anyhow::anyhow as e;
+use day07::{export, render, Machine};
+use std::fs::File;
+use std::io::BufReader;
+
+fn main() -> anyhow::Result<()> {
+    let mut filename = "input.txt".to_string();
+    let mut from = "transcript".to_string();
+    let mut to = "json".to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = args.next().ok_or_else(|| e!("--from requires a format"))?,
+            "--to" => to = args.next().ok_or_else(|| e!("--to requires a format"))?,
+            _ => filename = arg,
+        }
+    }
+    let reader = BufReader::new(File::open(&filename)?);
+    let machine = match from.as_str() {
+        "transcript" => Machine::from_transcript(reader)?,
+        "json" => Machine::from_json(&serde_json::from_reader(reader)?)?,
+        _ => return Err(e!("unknown input format {}", from)),
+    };
+    match to.as_str() {
+        "transcript" => print!("{}", export::to_transcript(&machine.fs)),
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&export::to_json(&machine.fs))?
+        ),
+        "tree" => render::tree(&machine.fs, false, &mut std::io::stdout())?,
+        _ => return Err(e!("unknown output format {}", to)),
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! Compare two snapshots of a device, each either a terminal transcript or a JSON export.
+//!
+//! Usage: `d7diff <before> <after>`
+use anyhow::anyhow as e;
+use day07::diff::diff;
+use day07::Machine;
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (before, after) = match &args[..] {
+        [before, after] => (Machine::load(before)?, Machine::load(after)?),
+        _ => return Err(e!("usage: d7diff <before> <after>")),
+    };
+    for difference in diff(&before.fs, &after.fs) {
+        println!("{}", difference);
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Run the day07 size calculations on a real directory.
+//!
+//! Usage: `d7du [-h] [--symlinks skip|link|follow] [--max-depth n] [--ignore pattern]...
+//! [--free n] [dir]`
+//!
+//! Lists the size of every directory, biggest first, and with `--free` the smallest directory
+//! that could be deleted to free that many bytes. `--max-depth` must be at least 1 and leaves
+//! directories at that depth empty, so the sizes only count what was scanned.
+use anyhow::anyhow as e;
+use day07::scan::ScanOptions;
+use day07::{format_path, human_size, render, FileSystem};
+
+fn main() -> anyhow::Result<()> {
+    let mut dir = ".".to_string();
+    let mut options = ScanOptions::default();
+    let mut human = false;
+    let mut free = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| e!("{} requires a value", arg));
+        match arg.as_str() {
+            "-h" => human = true,
+            "--symlinks" => options.symlinks = value()?.parse()?,
+            "--max-depth" => options.max_depth = Some(value()?.parse()?),
+            "--ignore" => options.ignore.push(value()?),
+            "--free" => free = Some(value()?.parse()?),
+            _ => dir = arg,
+        }
+    }
+    let fs = FileSystem::scan(&dir, &options)?;
+    render::du(&fs, &Vec::new(), human, true, &mut std::io::stdout())?;
+    if let Some(need) = free {
+        match fs.smallest_dir_at_least(need) {
+            Some((path, size)) => {
+                let shown = if human {
+                    human_size(size)
+                } else {
+                    size.to_string()
+                };
+                println!("delete {} to free {}", format_path(&path), shown)
+            }
+            None => println!("no directory holds {} bytes", need),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+//! Plan which files and directories to delete to make room for the update.
+//!
+//! Usage: `d7plan [--total n] [--required n] [--protect path]... [--max-states n] [filename]`
+use anyhow::anyhow as e;
+use day07::plan::plan_deletion;
+use day07::{format_path, resolve, Machine};
+use std::fs::File;
+use std::io::BufReader;
+
+fn main() -> anyhow::Result<()> {
+    let mut filename = "input.txt".to_string();
+    let mut total_space: usize = 70000000;
+    let mut required_space: usize = 30000000;
+    let mut protected = Vec::new();
+    let mut max_states: usize = 1 << 31;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| e!("{} requires a value", arg));
+        match arg.as_str() {
+            "--total" => total_space = value()?.parse()?,
+            "--required" => required_space = value()?.parse()?,
+            "--protect" => protected.push(resolve(&Vec::new(), &value()?)?),
+            "--max-states" => max_states = value()?.parse()?,
+            _ => filename = arg,
+        }
+    }
+    let f = File::open(&filename)?;
+    let machine = Machine::from_transcript(BufReader::new(f))?;
+    let used = machine.fs.du();
+    let free_space = total_space
+        .checked_sub(used)
+        .ok_or_else(|| e!("{} bytes used on a {} byte disk", used, total_space))?;
+    let need = required_space.saturating_sub(free_space);
+    println!(
+        "free {} of {} bytes, need {} more",
+        free_space, total_space, need
+    );
+    let plan = plan_deletion(&machine.fs, need, &protected, max_states)?.ok_or_else(|| {
+        e!(
+            "cannot free {} bytes without touching protected paths",
+            need
+        )
+    })?;
+    for (path, size) in &plan.entries {
+        println!("{:>10} {}", size, format_path(path));
+    }
+    println!(
+        "delete {} bytes, {} over, leaving {} bytes free",
+        plan.total,
+        plan.total - need,
+        free_space + plan.total
+    );
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Explore and modify a filesystem reconstructed from a terminal transcript.
+//!
+//! Usage: `d7shell [transcript]`, type `help` at the prompt for the list of commands.
+use day07::shell::Shell;
+use day07::Machine;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+
+fn main() -> anyhow::Result<()> {
+    let filename = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input.txt".to_string());
+    let f = File::open(&filename)?;
+    let mut machine = Machine::from_transcript(BufReader::new(f))?;
+    machine.cd("/")?;
+    let mut shell = Shell::new(machine);
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    loop {
+        write!(stdout, "{}", shell.prompt())?;
+        stdout.flush()?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            writeln!(stdout)?;
+            break;
+        }
+        match shell.execute(&line, &mut stdout) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Compare two snapshots of the same filesystem.
+use crate::{format_path, FileSystem, Path};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added(usize),
+    Removed(usize),
+    Resized(usize, usize),
+}
+
+/// A single entry that differs between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub path: Path,
+    pub is_dir: bool,
+    pub change: Change,
+}
+
+impl Difference {
+    /// How much bigger the entry got, negative when it shrank or was removed
+    pub fn delta(&self) -> i64 {
+        match self.change {
+            Change::Added(size) => size as i64,
+            Change::Removed(size) => -(size as i64),
+            Change::Resized(before, after) => after as i64 - before as i64,
+        }
+    }
+}
+
+/// `+ /a/new/ 1234`, `- /b.txt 14848514` or `~ /d/ 24933642 -> 17719346 (-7214296)`, with a
+/// trailing `/` on directories
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut path = format_path(&self.path);
+        if self.is_dir && !self.path.is_empty() {
+            path.push('/');
+        }
+        match self.change {
+            Change::Added(size) => write!(f, "+ {} {}", path, size),
+            Change::Removed(size) => write!(f, "- {} {}", path, size),
+            Change::Resized(before, after) => {
+                write!(f, "~ {} {} -> {} ({:+})", path, before, after, self.delta())
+            }
+        }
+    }
+}
+
+/// Everything that changed going from `before` to `after`, in pre-order with names sorted.
+///
+/// Added and removed directories are listed once with their total size rather than along with
+/// everything inside them. A directory that holds the same total can still contain changes, so
+/// directories are always compared entry by entry. An entry that turned from a file into a
+/// directory or back shows up as removed and added again.
+pub fn diff(before: &FileSystem, after: &FileSystem) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_walk(before, after, &mut Vec::new(), &mut differences);
+    differences
+}
+
+fn diff_walk(
+    before: &FileSystem,
+    after: &FileSystem,
+    path: &mut Path,
+    differences: &mut Vec<Difference>,
+) {
+    if before.is_dir() != after.is_dir() {
+        differences.push(Difference {
+            path: path.clone(),
+            is_dir: before.is_dir(),
+            change: Change::Removed(before.du()),
+        });
+        differences.push(Difference {
+            path: path.clone(),
+            is_dir: after.is_dir(),
+            change: Change::Added(after.du()),
+        });
+        return;
+    }
+    if before.du() != after.du() {
+        differences.push(Difference {
+            path: path.clone(),
+            is_dir: before.is_dir(),
+            change: Change::Resized(before.du(), after.du()),
+        });
+    }
+    let mut names: Vec<&String> = before
+        .children()
+        .into_iter()
+        .chain(after.children())
+        .flat_map(|children| children.keys())
+        .collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        path.push(name.clone());
+        match (before.get(name), after.get(name)) {
+            (Some(old), Some(new)) => diff_walk(old, new, path, differences),
+            (Some(old), None) => differences.push(Difference {
+                path: path.clone(),
+                is_dir: old.is_dir(),
+                change: Change::Removed(old.du()),
+            }),
+            (None, Some(new)) => differences.push(Difference {
+                path: path.clone(),
+                is_dir: new.is_dir(),
+                change: Change::Added(new.du()),
+            }),
+            (None, None) => unreachable!("names come from one of the two directories"),
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+    use crate::walk::{Order, Visit};
+
+    #[test]
+    fn test_diff() {
+        let before = example();
+        assert!(diff(&before.fs, &before.fs).is_empty());
+
+        let mut after = example();
+        after.fs.remove(&vec!["a".to_string()]).unwrap();
+        after
+            .fs
+            .insert(&Vec::new(), FileSystem::file("a", 10))
+            .unwrap();
+        after
+            .fs
+            .insert(&vec!["d".to_string()], FileSystem::dir("x"))
+            .unwrap();
+        after
+            .fs
+            .walk_mut(Order::Pre, |_, fs| match fs {
+                FileSystem::File(name, _) if name == "k" => Ok(Visit::Remove),
+                FileSystem::File(name, size) if name == "j" => {
+                    *size += 1000;
+                    Ok(Visit::Continue)
+                }
+                _ => Ok(Visit::Continue),
+            })
+            .unwrap();
+
+        let lines: Vec<String> = diff(&before.fs, &after.fs)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "~ / 48381165 -> 41073026 (-7308139)",
+                "- /a/ 94853",
+                "+ /a 10",
+                "~ /d/ 24933642 -> 17720346 (-7213296)",
+                "~ /d/j 4060174 -> 4061174 (+1000)",
+                "- /d/k 7214296",
+                "+ /d/x/ 0",
+            ]
+        );
+        let back = diff(&after.fs, &before.fs);
+        assert_eq!(back[0].delta(), 7308139);
+    }
+}
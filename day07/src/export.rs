@@ -0,0 +1,161 @@
+//! Convert a [`FileSystem`] to and from JSON, and back into a terminal transcript.
+//!
+//! Directories become `{"name": "a", "type": "dir", "children": [...]}` and files become
+//! `{"name": "b.txt", "type": "file", "size": 14848514}`, children in name order. The root
+//! directory is called `/`.
+use crate::{FileSystem, Machine};
+use anyhow::anyhow as e;
+use anyhow::Context;
+use serde_json::{json, Value};
+
+pub fn to_json(fs: &FileSystem) -> Value {
+    match fs {
+        FileSystem::Dir(name, _) => {
+            let children: Vec<Value> = fs.sorted_children().into_iter().map(to_json).collect();
+            let name = if name.is_empty() { "/" } else { name };
+            json!({"name": name, "type": "dir", "children": children})
+        }
+        FileSystem::File(name, size) => json!({"name": name, "type": "file", "size": size}),
+    }
+}
+
+/// Build a filesystem from the JSON written by [`to_json`]
+pub fn from_json(value: &Value) -> anyhow::Result<FileSystem> {
+    let fs = entry_from_json(value)?;
+    match fs {
+        FileSystem::Dir(name, contents) if name == "/" => {
+            Ok(FileSystem::Dir(String::new(), contents))
+        }
+        _ => Err(e!("the top level entry must be the directory /")),
+    }
+}
+
+fn entry_from_json(value: &Value) -> anyhow::Result<FileSystem> {
+    let name = value["name"].as_str().context("entry without a name")?;
+    match value["type"].as_str() {
+        Some("file") => {
+            let size = value["size"]
+                .as_u64()
+                .with_context(|| format!("file {} without a size", name))?;
+            Ok(FileSystem::file(name, size as usize))
+        }
+        Some("dir") => {
+            let mut dir = FileSystem::dir(name);
+            let children = value["children"]
+                .as_array()
+                .with_context(|| format!("directory {} without children", name))?;
+            for child in children {
+                let child = entry_from_json(child).with_context(|| format!("in {}", name))?;
+                if child.name().is_empty() || child.name().contains('/') || child.name() == ".." {
+                    return Err(e!("invalid name {:?} in {}", child.name(), name));
+                }
+                let entries = dir.children_mut().expect("just created a directory");
+                if entries.contains_key(child.name()) {
+                    return Err(e!("{} appears twice in {}", child.name(), name));
+                }
+                entries.insert(child.name().to_string(), child);
+            }
+            Ok(dir)
+        }
+        _ => Err(e!("{} is neither a file nor a dir", name)),
+    }
+}
+
+/// Write the shortest transcript of `$ cd` and `$ ls` commands that rebuilds `fs`.
+///
+/// Every directory with something in it is listed exactly once, empty directories only show up
+/// in their parent's listing.
+pub fn to_transcript(fs: &FileSystem) -> String {
+    let mut transcript = String::from("$ cd /\n");
+    transcript_walk(fs, &mut transcript);
+    // there is no need to climb back out of the last directory visited
+    while let Some(rest) = transcript.strip_suffix("$ cd ..\n") {
+        transcript.truncate(rest.len());
+    }
+    transcript
+}
+
+fn transcript_walk(fs: &FileSystem, transcript: &mut String) {
+    let children = fs.sorted_children();
+    transcript.push_str("$ ls\n");
+    for child in &children {
+        match child {
+            FileSystem::Dir(name, _) => transcript.push_str(&format!("dir {}\n", name)),
+            FileSystem::File(name, size) => transcript.push_str(&format!("{} {}\n", size, name)),
+        }
+    }
+    for child in children {
+        if matches!(child.children(), Some(entries) if !entries.is_empty()) {
+            transcript.push_str(&format!("$ cd {}\n", child.name()));
+            transcript_walk(child, transcript);
+            transcript.push_str("$ cd ..\n");
+        }
+    }
+}
+
+impl Machine {
+    /// A machine holding the filesystem from [`from_json`], sitting in the root directory
+    pub fn from_json(value: &Value) -> anyhow::Result<Self> {
+        Ok(Machine {
+            fs: from_json(value)?,
+            cwd: Vec::new(),
+        })
+    }
+
+    /// Read either a terminal transcript or a JSON snapshot, whichever `filename` holds
+    pub fn load(filename: &str) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(filename)
+            .with_context(|| format!("cannot read {}", filename))?;
+        if text.trim_start().starts_with('{') {
+            let value = serde_json::from_str(&text).with_context(|| filename.to_string())?;
+            Machine::from_json(&value).with_context(|| filename.to_string())
+        } else {
+            Machine::from_transcript(text.as_bytes()).with_context(|| filename.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+
+    #[test]
+    fn test_json_round_trip() {
+        let machine = example();
+        let json = to_json(&machine.fs);
+        assert_eq!(json["name"], "/");
+        assert_eq!(json["children"][0]["name"], "a");
+        assert_eq!(json["children"][1]["size"], 14848514);
+        let text = serde_json::to_string(&json).unwrap();
+        let parsed = Machine::from_json(&serde_json::from_str(&text).unwrap()).unwrap();
+        assert_eq!(parsed.fs.du(), 48381165);
+        assert_eq!(to_json(&parsed.fs), json);
+
+        assert!(from_json(&json!({"name": "a", "type": "dir", "children": []})).is_err());
+        let duplicate = json!({"name": "/", "type": "dir", "children": [
+            {"name": "x", "type": "file", "size": 1},
+            {"name": "x", "type": "dir", "children": []},
+        ]});
+        assert!(from_json(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let mut machine = example();
+        machine
+            .fs
+            .insert(&Vec::new(), FileSystem::dir("empty"))
+            .unwrap();
+        let transcript = to_transcript(&machine.fs);
+        assert!(transcript.starts_with("$ cd /\n$ ls\ndir a\n14848514 b.txt\n"));
+        assert!(!transcript.contains("$ cd empty"));
+        assert!(!transcript.ends_with("$ cd ..\n"));
+
+        let rebuilt = Machine::from_transcript(transcript.as_bytes()).unwrap();
+        assert_eq!(to_json(&rebuilt.fs), to_json(&machine.fs));
+        assert_eq!(to_transcript(&rebuilt.fs), transcript);
+        let again = Machine::from_transcript(to_transcript(&rebuilt.fs).as_bytes()).unwrap();
+        assert_eq!(to_json(&again.fs), to_json(&machine.fs));
+    }
+}
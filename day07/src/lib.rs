@@ -0,0 +1,484 @@
+use anyhow::anyhow as e;
+use anyhow::Context;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::ops::Deref;
+use std::str::FromStr;
+
+pub mod diff;
+pub mod export;
+pub mod plan;
+pub mod render;
+pub mod scan;
+pub mod shell;
+pub mod walk;
+
+/// The names of the directories leading to an entry, starting from the root
+pub type Path = Vec<String>;
+
+pub enum InputLine {
+    Cd(String),
+    Ls,
+    File(usize, String),
+    Dir(String),
+}
+
+impl FromStr for InputLine {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_end();
+        let parts: Vec<&str> = s.split(|c: char| c.is_whitespace()).collect();
+        match parts[..] {
+            ["$", "ls"] => Ok(InputLine::Ls),
+            ["$", "cd", dir] => Ok(InputLine::Cd(dir.to_string())),
+            ["dir", name] => Ok(InputLine::Dir(name.to_string())),
+            [size, name] => {
+                let size = size.parse::<usize>()?;
+                Ok(InputLine::File(size, name.to_string()))
+            }
+            _ => Err(e!("unknown command {}", s)),
+        }
+    }
+}
+
+/// Resolve `target` against the directory `cwd`.
+///
+/// Paths starting with `/` are absolute, anything else is relative to `cwd`. `.` and empty
+/// components are skipped and `..` moves up a directory, which is an error at the root.
+pub fn resolve(cwd: &Path, target: &str) -> anyhow::Result<Path> {
+    let mut path = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        cwd.clone()
+    };
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                path.pop()
+                    .with_context(|| format!("no parent directory resolving {}", target))?;
+            }
+            name => path.push(name.to_string()),
+        }
+    }
+    Ok(path)
+}
+
+/// Format a path the way a shell would show it
+pub fn format_path(path: &Path) -> String {
+    let mut s = path.join("/");
+    s.insert(0, '/');
+    s
+}
+
+/// A directory or file. Directories cache their total size the first time [`FileSystem::du`]
+/// asks for it, see [`Contents`] for how the cache is kept up to date.
+pub enum FileSystem {
+    Dir(String, Contents),
+    File(String, usize),
+}
+
+/// The entries of a directory by name, along with its cached total size.
+///
+/// Entries can be read through `Deref` but only changed through [`FileSystem::children_mut`],
+/// which forgets the cached size. Reaching a directory mutably means calling it on every
+/// directory above it too, so a change anywhere in the tree is always reflected by `du`.
+#[derive(Default)]
+pub struct Contents {
+    entries: HashMap<String, FileSystem>,
+    size: Cell<Option<usize>>,
+}
+
+impl Deref for Contents {
+    type Target = HashMap<String, FileSystem>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl FileSystem {
+    /// An empty directory
+    pub fn dir(name: &str) -> Self {
+        Self::Dir(name.to_string(), Contents::default())
+    }
+
+    pub fn file(name: &str, size: usize) -> Self {
+        Self::File(name.to_string(), size)
+    }
+
+    /// Change the name of this entry. Directories look their children up by name, so renaming
+    /// anything but the root should happen inside [`FileSystem::walk_mut`], which files the
+    /// entry under its new name once it has been visited.
+    pub fn set_name(&mut self, new_name: &str) {
+        match self {
+            Self::Dir(name, _) | Self::File(name, _) => *name = new_name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Dir(name, _) => name,
+            Self::File(name, _) => name,
+        }
+    }
+
+    pub fn get<'a>(&'a self, name: &str) -> Option<&'a Self> {
+        self.children()?.get(name)
+    }
+
+    /// Mutable access to a child, forgetting the cached size of this directory
+    pub fn get_mut<'a>(&'a mut self, name: &str) -> Option<&'a mut Self> {
+        self.children_mut()?.get_mut(name)
+    }
+
+    pub fn get_path<'a>(&'a self, path: &Path) -> Option<&'a Self> {
+        let path = if path.first() == Some(&"".to_string()) {
+            &path[1..]
+        } else {
+            &path[..]
+        };
+        let mut ptr = self;
+        for part in path {
+            match ptr.get(part) {
+                Some(fs) => ptr = fs,
+                None => return None,
+            }
+        }
+        Some(ptr)
+    }
+
+    pub fn get_path_mut<'a>(&'a mut self, path: &Path) -> Option<&'a mut Self> {
+        let path = if path.first() == Some(&"".to_string()) {
+            &path[1..]
+        } else {
+            &path[..]
+        };
+        let mut ptr = self;
+        for part in path {
+            match ptr.get_mut(part) {
+                Some(fs) => ptr = fs,
+                None => return None,
+            }
+        }
+        Some(ptr)
+    }
+
+    pub fn insert(&mut self, parent_path: &Path, fs: FileSystem) -> anyhow::Result<()> {
+        let parent = self
+            .get_path_mut(parent_path)
+            .context("path does not exist")?;
+        let children = parent.children_mut().context("path is not a directory")?;
+        children.insert(fs.name().to_string(), fs);
+        Ok(())
+    }
+
+    /// Take the entry at `path` out of the filesystem
+    pub fn remove(&mut self, path: &Path) -> anyhow::Result<FileSystem> {
+        let (name, parent_path) = path
+            .split_last()
+            .context("cannot remove the root directory")?;
+        let parent = self
+            .get_path_mut(&parent_path.to_vec())
+            .context("path does not exist")?;
+        let children = parent.children_mut().context("path is not a directory")?;
+        children
+            .remove(name)
+            .with_context(|| format!("no such file or directory: {}", format_path(path)))
+    }
+
+    /// Children of a directory ordered by name, empty for files
+    pub fn sorted_children(&self) -> Vec<&FileSystem> {
+        let mut children: Vec<&FileSystem> = self
+            .children()
+            .map(|children| children.values().collect())
+            .unwrap_or_default();
+        children.sort_by(|a, b| a.name().cmp(b.name()));
+        children
+    }
+
+    pub fn children(&self) -> Option<&HashMap<String, Self>> {
+        if let Self::Dir(_, contents) = self {
+            return Some(contents);
+        }
+        None
+    }
+
+    /// The only way to change the entries of a directory, forgetting its cached size
+    pub fn children_mut(&mut self) -> Option<&mut HashMap<String, Self>> {
+        if let Self::Dir(_, contents) = self {
+            contents.size.set(None);
+            return Some(&mut contents.entries);
+        }
+        None
+    }
+
+    pub fn is_dir(&self) -> bool {
+        if let FileSystem::Dir(_, _) = self {
+            return true;
+        }
+        false
+    }
+}
+
+impl<'a> IntoIterator for &'a FileSystem {
+    type Item = &'a FileSystem;
+    type IntoIter = FileSystemIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FileSystemIterator(vec![self])
+    }
+}
+
+pub struct FileSystemIterator<'a>(Vec<&'a FileSystem>);
+
+impl<'a> Iterator for FileSystemIterator<'a> {
+    type Item = &'a FileSystem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.0.pop();
+        match n {
+            Some(FileSystem::File(_, _)) => n,
+            Some(FileSystem::Dir(_, children)) => {
+                self.0.extend(children.values());
+                n
+            }
+            None => None,
+        }
+    }
+}
+
+impl FileSystem {
+    /// Total size of all the files below this entry.
+    ///
+    /// Computing the size of a directory also caches the size of every directory below it, so
+    /// asking for the size of every directory in the tree takes linear time overall.
+    pub fn du(&self) -> usize {
+        match self {
+            FileSystem::File(_, size) => *size,
+            FileSystem::Dir(_, contents) => match contents.size.get() {
+                Some(size) => size,
+                None => {
+                    let size = contents.values().map(|child| child.du()).sum();
+                    contents.size.set(Some(size));
+                    size
+                }
+            },
+        }
+    }
+
+    /// The smallest directory holding at least `need` bytes, deleting it frees enough space
+    pub fn smallest_dir_at_least(&self, need: usize) -> Option<(Path, usize)> {
+        render::dir_sizes(self, &Vec::new())
+            .into_iter()
+            .filter(|&(_, size)| size >= need)
+            .min_by(|(a, a_size), (b, b_size)| a_size.cmp(b_size).then_with(|| a.cmp(b)))
+    }
+}
+
+/// Format a size in bytes with a binary unit suffix, like `du -h`
+pub fn human_size(size: usize) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+pub struct Machine {
+    pub fs: FileSystem,
+    pub cwd: Path,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Machine {
+            cwd: Vec::new(),
+            fs: FileSystem::dir(""),
+        }
+    }
+
+    /// Rebuild a filesystem from a terminal transcript
+    pub fn from_transcript(reader: impl BufRead) -> anyhow::Result<Self> {
+        let mut machine = Machine::new();
+        for (i, line) in reader.lines().enumerate() {
+            let cmd: InputLine = line?.parse().with_context(|| format!("line {}", i + 1))?;
+            machine
+                .run(&cmd)
+                .with_context(|| format!("line {}", i + 1))?;
+        }
+        Ok(machine)
+    }
+
+    pub fn run(&mut self, cmd: &InputLine) -> anyhow::Result<()> {
+        match cmd {
+            InputLine::Ls => Ok(()),
+            InputLine::Cd(dir) => self.cd(dir),
+            // listing a directory twice must not wipe out what we already know is inside it
+            InputLine::Dir(name) if self.fs.get_path(&resolve(&self.cwd, name)?).is_some() => {
+                Ok(())
+            }
+            InputLine::Dir(name) => self.fs.insert(&self.cwd, FileSystem::dir(name)),
+            InputLine::File(size, name) => self.fs.insert(&self.cwd, FileSystem::file(name, *size)),
+        }
+    }
+
+    pub fn cd(&mut self, dir: &str) -> anyhow::Result<()> {
+        let path = resolve(&self.cwd, dir)?;
+        match self.fs.get_path(&path) {
+            Some(fs) if fs.is_dir() => {
+                self.cwd = path;
+                Ok(())
+            }
+            Some(_) => Err(e!("not a directory: {}", format_path(&path))),
+            None => Err(e!("no such directory: {}", format_path(&path))),
+        }
+    }
+
+    pub fn pwd(&self) -> String {
+        format_path(&self.cwd)
+    }
+}
+
+/// Fixtures shared by the tests of every module
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// The filesystem from the puzzle's example transcript
+    pub(crate) fn example() -> Machine {
+        let example = include_str!("../example.txt");
+        Machine::from_transcript(example.as_bytes()).unwrap()
+    }
+
+    /// Split a relative path like `a/e` into its names
+    pub(crate) fn path(s: &str) -> Path {
+        s.split('/').map(|part| part.to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+
+    #[test]
+    fn test_example_sizes() {
+        let machine = example();
+        assert_eq!(machine.fs.du(), 48381165);
+        let small: usize = (&machine.fs)
+            .into_iter()
+            .filter(|fs| fs.is_dir())
+            .map(|dir| dir.du())
+            .filter(|&size| size < 100000)
+            .sum();
+        assert_eq!(small, 95437);
+        assert_eq!(
+            machine.fs.smallest_dir_at_least(8381165),
+            Some((vec!["d".to_string()], 24933642))
+        );
+        assert_eq!(machine.fs.smallest_dir_at_least(50000000), None);
+    }
+
+    #[test]
+    fn test_cached_sizes() {
+        let mut machine = example();
+        let a = vec!["a".to_string()];
+        assert_eq!(machine.fs.get_path(&a).unwrap().du(), 94853);
+        machine
+            .fs
+            .insert(
+                &vec!["a".to_string(), "e".to_string()],
+                FileSystem::file("j", 16),
+            )
+            .unwrap();
+        assert_eq!(machine.fs.get_path(&a).unwrap().du(), 94869);
+        assert_eq!(machine.fs.du(), 48381181);
+        machine.fs.remove(&vec!["d".to_string()]).unwrap();
+        assert_eq!(machine.fs.du(), 23447539);
+        if let Some(FileSystem::File(_, size)) = machine.fs.get_path_mut(&vec!["b.txt".to_string()])
+        {
+            *size = 0;
+        }
+        assert_eq!(machine.fs.du(), 8599025);
+        assert_eq!(machine.fs.get_path(&a).unwrap().du(), 94869);
+
+        // changing a directory reached through get_path_mut, with every size already cached
+        let a_dir = machine.fs.get_path_mut(&a).unwrap();
+        let children = a_dir.children_mut().unwrap();
+        children.insert("z".to_string(), FileSystem::file("z", 1000));
+        children.remove("f");
+        assert_eq!(machine.fs.get_path(&a).unwrap().du(), 94869 + 1000 - 29116);
+        assert_eq!(machine.fs.du(), 8599025 + 1000 - 29116);
+        if let Some(FileSystem::File(_, size)) =
+            machine
+                .fs
+                .get_path_mut(&vec!["a".to_string(), "e".to_string(), "i".to_string()])
+        {
+            *size += 1;
+        }
+        assert_eq!(
+            machine.fs.get_path(&a).unwrap().du(),
+            94869 + 1000 - 29116 + 1
+        );
+        assert_eq!(machine.fs.du(), 8599025 + 1000 - 29116 + 1);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let cwd = vec!["a".to_string(), "e".to_string()];
+        assert_eq!(resolve(&cwd, "/").unwrap(), Vec::<String>::new());
+        assert_eq!(resolve(&cwd, "..").unwrap(), vec!["a"]);
+        assert_eq!(resolve(&cwd, "../../d").unwrap(), vec!["d"]);
+        assert_eq!(resolve(&cwd, "/d/./x/").unwrap(), vec!["d", "x"]);
+        assert_eq!(resolve(&cwd, "f").unwrap(), vec!["a", "e", "f"]);
+        assert!(resolve(&cwd, "../../..").is_err());
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(584), "584");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(14848514), "14M");
+        assert_eq!(human_size(48381165), "46M");
+    }
+
+    #[test]
+    fn test_repeated_ls() {
+        let mut machine = example();
+        machine.cd("/").unwrap();
+        machine.run(&"dir a".parse().unwrap()).unwrap();
+        assert_eq!(machine.fs.du(), 48381165);
+    }
+
+    #[test]
+    fn test_cd() {
+        let mut machine = example();
+        assert_eq!(machine.pwd(), "/d");
+        machine.cd("/a/e").unwrap();
+        assert_eq!(machine.pwd(), "/a/e");
+        machine.cd("../..").unwrap();
+        assert_eq!(machine.pwd(), "/");
+        assert!(machine.cd("missing").is_err());
+        assert!(machine.cd("b.txt").is_err());
+        assert!(machine.cd("..").is_err());
+        assert_eq!(machine.pwd(), "/");
+    }
+}
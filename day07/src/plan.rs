@@ -0,0 +1,251 @@
+//! Choose what to delete to free up a given amount of space.
+use crate::{FileSystem, Path};
+use anyhow::anyhow as e;
+
+/// Entries to delete, none of them inside another, with their sizes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub entries: Vec<(Path, usize)>,
+    pub total: usize,
+}
+
+/// Something that could be deleted, in pre-order so the entries inside it follow it directly
+struct Entry {
+    path: Path,
+    size: usize,
+    deletable: bool,
+    /// Index of the first entry after everything inside this one
+    end: usize,
+}
+
+fn collect_entries(fs: &FileSystem, path: &mut Path, protected: &[Path], entries: &mut Vec<Entry>) {
+    for child in fs.sorted_children() {
+        path.push(child.name().to_string());
+        let index = entries.len();
+        entries.push(Entry {
+            path: path.clone(),
+            size: child.du(),
+            deletable: !protected
+                .iter()
+                .any(|p| p.starts_with(path) || path.starts_with(p)),
+            end: 0,
+        });
+        collect_entries(child, path, protected, entries);
+        entries[index].end = entries.len();
+        path.pop();
+    }
+}
+
+/// The set of totals from 0 to some limit that can be freed
+#[derive(Clone)]
+struct Totals(Vec<u64>);
+
+impl Totals {
+    fn new(limit: usize) -> Self {
+        Totals(vec![0; limit / 64 + 1])
+    }
+
+    fn contains(&self, total: usize) -> bool {
+        matches!(self.0.get(total / 64), Some(word) if word & (1 << (total % 64)) != 0)
+    }
+
+    fn insert(&mut self, total: usize) {
+        self.0[total / 64] |= 1 << (total % 64);
+    }
+
+    /// Add every total in `other` increased by `size`, the limit drops anything too big
+    fn add_shifted(&mut self, other: &Totals, size: usize) {
+        let (words, bits) = (size / 64, size % 64);
+        for i in (words..self.0.len()).rev() {
+            let mut word = other.0[i - words] << bits;
+            if bits > 0 && i > words {
+                word |= other.0[i - words - 1] >> (64 - bits);
+            }
+            self.0[i] |= word;
+        }
+    }
+}
+
+/// Find the files and directories to delete that free at least `need` bytes with as little
+/// over-deletion as possible, deleting whole directories rather than what is inside them when
+/// that frees the same amount.
+///
+/// Nothing inside a protected path is ever deleted, and neither is a directory containing one.
+/// The root directory is never deleted as a whole. Finding the best plan is a subset sum
+/// problem solved by tracking every total each entry onwards can free up to the size of the
+/// best single deletion, so this gives up with an error when that would take more than
+/// `max_states` entry and total pairs. `Ok(None)` means there is no way to free enough space.
+pub fn plan_deletion(
+    fs: &FileSystem,
+    need: usize,
+    protected: &[Path],
+    max_states: usize,
+) -> anyhow::Result<Option<Plan>> {
+    let mut all = Vec::new();
+    collect_entries(fs, &mut Vec::new(), protected, &mut all);
+    let n = all.len();
+
+    // deleting the smallest single entry that is big enough is always possible and otherwise
+    // everything that can be deleted is the most we could ever free
+    let mut most = 0;
+    let mut i = 0;
+    while i < n {
+        if all[i].deletable {
+            most += all[i].size;
+            i = all[i].end;
+        } else {
+            i += 1;
+        }
+    }
+    if most < need {
+        return Ok(None);
+    }
+    let limit = all
+        .iter()
+        .filter(|entry| entry.deletable && entry.size >= need)
+        .map(|entry| entry.size)
+        .min()
+        .unwrap_or(most);
+    if (n + 1).saturating_mul(limit + 1) > max_states {
+        return Err(e!(
+            "gave up, {} entries with totals up to {} is more than {} states",
+            n,
+            limit,
+            max_states
+        ));
+    }
+
+    // totals[i] holds what can be freed using only entries from i onwards
+    let mut totals = vec![Totals::new(limit); n + 1];
+    totals[n].insert(0);
+    for i in (0..n).rev() {
+        let mut reachable = totals[i + 1].clone();
+        if all[i].deletable && all[i].size <= limit {
+            reachable.add_shifted(&totals[all[i].end], all[i].size);
+        }
+        totals[i] = reachable;
+    }
+    let best = (need..=limit)
+        .find(|&total| totals[0].contains(total))
+        .expect("the limit itself can always be freed");
+
+    let mut plan = Plan::default();
+    let (mut i, mut remaining) = (0, best);
+    while remaining > 0 {
+        let entry = &all[i];
+        if entry.deletable
+            && entry.size <= remaining
+            && totals[entry.end].contains(remaining - entry.size)
+        {
+            plan.entries.push((entry.path.clone(), entry.size));
+            plan.total += entry.size;
+            remaining -= entry.size;
+            i = entry.end;
+        } else {
+            i += 1;
+        }
+    }
+    Ok(Some(plan))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render;
+    use crate::testing::{example, path};
+
+    /// Try every set of entries that are not inside each other
+    fn brute_force(fs: &FileSystem, need: usize, protected: &[Path]) -> Option<usize> {
+        let mut entries: Vec<(Path, usize)> = render::dir_sizes(fs, &Vec::new())
+            .into_iter()
+            .filter(|(path, _)| !path.is_empty())
+            .collect();
+        let mut stack = vec![(Vec::new(), fs)];
+        while let Some((path, dir)) = stack.pop() {
+            for child in dir.sorted_children() {
+                let mut child_path = path.clone();
+                child_path.push(child.name().to_string());
+                match child {
+                    FileSystem::File(_, size) => entries.push((child_path, *size)),
+                    FileSystem::Dir(_, _) => stack.push((child_path, child)),
+                }
+            }
+        }
+        entries.retain(|(path, _)| {
+            !protected
+                .iter()
+                .any(|p| p.starts_with(path) || path.starts_with(p))
+        });
+        let mut best = None;
+        for mask in 0..1usize << entries.len() {
+            let chosen: Vec<&(Path, usize)> = (0..entries.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| &entries[i])
+                .collect();
+            let nested = chosen.iter().any(|(a, _)| {
+                chosen
+                    .iter()
+                    .any(|(b, _)| a != b && a.starts_with(b.as_slice()))
+            });
+            let total: usize = chosen.iter().map(|(_, size)| size).sum();
+            if !nested && total >= need && !matches!(best, Some(best) if total >= best) {
+                best = Some(total);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_example_plan() {
+        let machine = example();
+        // the puzzle needs 8381165 bytes, deleting d frees 24933642 but c.dat is a closer fit
+        let plan = plan_deletion(&machine.fs, 8381165, &[], 1 << 30)
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(plan.total), brute_force(&machine.fs, 8381165, &[]));
+        assert!(plan.total < 24933642);
+        for (path, size) in &plan.entries {
+            assert_eq!(machine.fs.get_path(path).unwrap().du(), *size);
+        }
+        assert_eq!(
+            plan.entries.iter().map(|(_, size)| size).sum::<usize>(),
+            plan.total
+        );
+
+        let protected = [path("c.dat"), path("d/k")];
+        let plan = plan_deletion(&machine.fs, 8381165, &protected, 1 << 30)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(plan.total),
+            brute_force(&machine.fs, 8381165, &protected)
+        );
+        for (path, _) in &plan.entries {
+            assert!(!protected
+                .iter()
+                .any(|p| p.starts_with(path) || path.starts_with(p)));
+        }
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let machine = example();
+        for need in [0, 1, 584, 2600, 65000, 100000, 5000000, 12000000, 30000000] {
+            for protected in [vec![], vec![path("a")], vec![path("b.txt"), path("d/j")]] {
+                let plan = plan_deletion(&machine.fs, need, &protected, 1 << 30).unwrap();
+                assert_eq!(
+                    plan.map(|plan| plan.total),
+                    brute_force(&machine.fs, need, &protected),
+                    "need {} protecting {:?}",
+                    need,
+                    protected
+                );
+            }
+        }
+        assert_eq!(
+            plan_deletion(&machine.fs, 50000000, &[], 1000).unwrap(),
+            None
+        );
+        assert!(plan_deletion(&machine.fs, 30000000, &[], 1000).is_err());
+    }
+}
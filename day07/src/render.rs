@@ -0,0 +1,138 @@
+//! Deterministic text renderings of a [`FileSystem`].
+//!
+//! Children are always visited in name order so the output does not depend on the order the
+//! underlying `HashMap` happens to iterate in.
+use crate::{format_path, human_size, FileSystem, Path};
+use std::io::prelude::*;
+
+/// Draw `fs` as an indented tree in the puzzle's own format, e.g. `- i (file, size=584)`.
+///
+/// With `sizes` directories also show their total size as `- a (dir, size=94853)`.
+pub fn tree(fs: &FileSystem, sizes: bool, out: &mut impl Write) -> std::io::Result<()> {
+    tree_walk(fs, 0, sizes, out)
+}
+
+fn tree_walk(
+    fs: &FileSystem,
+    depth: usize,
+    sizes: bool,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let indent = "  ".repeat(depth);
+    match fs {
+        FileSystem::Dir(name, _) => {
+            let name = if name.is_empty() { "/" } else { name };
+            if sizes {
+                writeln!(out, "{}- {} (dir, size={})", indent, name, fs.du())?;
+            } else {
+                writeln!(out, "{}- {} (dir)", indent, name)?;
+            }
+            for child in fs.sorted_children() {
+                tree_walk(child, depth + 1, sizes, out)?;
+            }
+        }
+        FileSystem::File(name, size) => {
+            writeln!(out, "{}- {} (file, size={})", indent, name, size)?
+        }
+    }
+    Ok(())
+}
+
+/// The total size of every directory below and including `fs`, which lives at `path`.
+///
+/// Directories come after their contents and siblings are in name order, like `du`.
+pub fn dir_sizes(fs: &FileSystem, path: &Path) -> Vec<(Path, usize)> {
+    let mut sizes = Vec::new();
+    dir_sizes_walk(fs, &mut path.clone(), &mut sizes);
+    sizes
+}
+
+fn dir_sizes_walk(fs: &FileSystem, path: &mut Path, sizes: &mut Vec<(Path, usize)>) {
+    if !fs.is_dir() {
+        return;
+    }
+    for child in fs.sorted_children() {
+        path.push(child.name().to_string());
+        dir_sizes_walk(child, path, sizes);
+        path.pop();
+    }
+    sizes.push((path.clone(), fs.du()));
+}
+
+/// Print a `du` style listing of `size<TAB>path` lines for the directories in `fs`.
+///
+/// `human` shows sizes with binary units like `du -h` and `by_size` lists the biggest
+/// directories first instead of walking the tree, breaking ties by path.
+pub fn du(
+    fs: &FileSystem,
+    path: &Path,
+    human: bool,
+    by_size: bool,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut sizes = dir_sizes(fs, path);
+    if by_size {
+        sizes.sort_by(|(a, a_size), (b, b_size)| b_size.cmp(a_size).then_with(|| a.cmp(b)));
+    }
+    for (path, size) in sizes {
+        let shown = if human {
+            human_size(size)
+        } else {
+            size.to_string()
+        };
+        writeln!(out, "{}\t{}", shown, format_path(&path))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+
+    fn render(f: impl FnOnce(&mut Vec<u8>) -> std::io::Result<()>) -> String {
+        let mut out = Vec::new();
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_tree() {
+        let machine = example();
+        // exactly the drawing from the puzzle description
+        let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+        for _ in 0..3 {
+            assert_eq!(render(|out| tree(&machine.fs, false, out)), expected);
+        }
+        let sized = render(|out| tree(machine.fs.get("a").unwrap(), true, out));
+        assert!(sized.starts_with("- a (dir, size=94853)\n  - e (dir, size=584)\n"));
+    }
+
+    #[test]
+    fn test_du() {
+        let machine = example();
+        assert_eq!(
+            render(|out| du(&machine.fs, &Vec::new(), false, false, out)),
+            "584\t/a/e\n94853\t/a\n24933642\t/d\n48381165\t/\n"
+        );
+        assert_eq!(
+            render(|out| du(&machine.fs, &Vec::new(), true, true, out)),
+            "46M\t/\n24M\t/d\n93K\t/a\n584\t/a/e\n"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Build a [`FileSystem`] from a real directory on disk.
+use crate::FileSystem;
+use anyhow::anyhow as e;
+use anyhow::Context;
+use std::fs;
+use std::str::FromStr;
+
+/// What to do with symbolic links found while scanning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symlinks {
+    /// Leave them out
+    Skip,
+    /// Count the link itself as a small file, like `du -P`
+    #[default]
+    Link,
+    /// Scan whatever they point to, links back to a directory being scanned are skipped
+    Follow,
+}
+
+impl FromStr for Symlinks {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Symlinks::Skip),
+            "link" => Ok(Symlinks::Link),
+            "follow" => Ok(Symlinks::Follow),
+            _ => Err(e!("symlinks must be skip, link or follow, not {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub symlinks: Symlinks,
+    /// Entries deeper than this are left out, the contents of the scanned directory are at
+    /// depth 1 and the limit must be at least that.
+    ///
+    /// Directories at the limit are kept but empty, so their sizes and those of everything above
+    /// them only count what was scanned.
+    pub max_depth: Option<usize>,
+    /// Names to leave out, `*` matches any run of characters and `?` any single character
+    pub ignore: Vec<String>,
+}
+
+impl ScanOptions {
+    fn ignored(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
+/// Match a whole name against a pattern with `*` and `?` wildcards
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // position in the pattern just after the last `*` and where in the name it started matching
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // let the last `*` swallow one more character and try again
+                Some((after, start)) => {
+                    star = Some((after, start + 1));
+                    p = after;
+                    n = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl FileSystem {
+    /// Scan the directory at `root` into a filesystem whose root directory is `root`.
+    pub fn scan(root: impl AsRef<std::path::Path>, options: &ScanOptions) -> anyhow::Result<Self> {
+        if options.max_depth == Some(0) {
+            return Err(e!("max depth must be at least 1"));
+        }
+        let root = root.as_ref();
+        let metadata =
+            fs::metadata(root).with_context(|| format!("cannot read {}", root.display()))?;
+        if !metadata.is_dir() {
+            return Err(e!("{} is not a directory", root.display()));
+        }
+        let mut dir = FileSystem::dir("");
+        let mut ancestors = vec![root.canonicalize()?];
+        scan_dir(&mut dir, root, 1, options, &mut ancestors)?;
+        Ok(dir)
+    }
+}
+
+fn scan_dir(
+    dir: &mut FileSystem,
+    path: &std::path::Path,
+    depth: usize,
+    options: &ScanOptions,
+    ancestors: &mut Vec<std::path::PathBuf>,
+) -> anyhow::Result<()> {
+    let entries = fs::read_dir(path).with_context(|| format!("cannot read {}", path.display()))?;
+    for entry in entries {
+        let entry = entry.with_context(|| format!("cannot read {}", path.display()))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if options.ignored(&name) {
+            continue;
+        }
+        let entry_path = entry.path();
+        let mut metadata = entry
+            .metadata()
+            .with_context(|| format!("cannot read {}", entry_path.display()))?;
+        if metadata.is_symlink() {
+            match options.symlinks {
+                Symlinks::Skip => continue,
+                Symlinks::Link => {}
+                Symlinks::Follow => match fs::metadata(&entry_path) {
+                    Ok(target) => metadata = target,
+                    // a dangling link has nothing to follow
+                    Err(_) => continue,
+                },
+            }
+        }
+        if !metadata.is_dir() {
+            dir.children_mut()
+                .expect("only directories are scanned")
+                .insert(
+                    name.clone(),
+                    FileSystem::file(&name, metadata.len() as usize),
+                );
+            continue;
+        }
+        let mut child = FileSystem::dir(&name);
+        if !matches!(options.max_depth, Some(max) if depth >= max) {
+            let real = entry_path.canonicalize()?;
+            if ancestors.contains(&real) {
+                // following this link would loop forever
+                continue;
+            }
+            ancestors.push(real);
+            scan_dir(&mut child, &entry_path, depth + 1, options, ancestors)?;
+            ancestors.pop();
+        }
+        dir.children_mut()
+            .expect("only directories are scanned")
+            .insert(name, child);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::path;
+
+    /// A scratch directory that is removed again when the test is done
+    struct Scratch(std::path::PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("day07-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(path.join("a/e")).unwrap();
+            fs::create_dir_all(path.join("d")).unwrap();
+            fs::write(path.join("b.txt"), vec![0; 1000]).unwrap();
+            fs::write(path.join("a/f"), vec![0; 200]).unwrap();
+            fs::write(path.join("a/e/i"), vec![0; 30]).unwrap();
+            fs::write(path.join("d/j.log"), vec![0; 4]).unwrap();
+            Scratch(path)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.log", "j.log"));
+        assert!(glob_match("target", "target"));
+        assert!(glob_match("a?c*", "abcdef"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("*.log", "j.log.gz"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_scan() {
+        let scratch = Scratch::new("scan");
+        let fs = FileSystem::scan(&scratch.0, &ScanOptions::default()).unwrap();
+        assert_eq!(fs.du(), 1234);
+        assert_eq!(fs.get_path(&path("a")).unwrap().du(), 230);
+        assert_eq!(fs.get_path(&path("a/e/i")).unwrap().du(), 30);
+
+        let options = ScanOptions {
+            max_depth: Some(1),
+            ignore: vec!["*.log".to_string()],
+            ..Default::default()
+        };
+        let fs = FileSystem::scan(&scratch.0, &options).unwrap();
+        // the files in a and d are below the limit so their sizes are not counted
+        assert_eq!(fs.du(), 1000);
+        assert!(fs
+            .get_path(&path("a"))
+            .unwrap()
+            .children()
+            .unwrap()
+            .is_empty());
+        assert!(fs.get_path(&path("d")).unwrap().is_dir());
+
+        let options = ScanOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let fs = FileSystem::scan(&scratch.0, &options).unwrap();
+        assert_eq!(fs.du(), 1204);
+        assert_eq!(fs.get_path(&path("a")).unwrap().du(), 200);
+        assert_eq!(fs.get_path(&path("a/e")).unwrap().du(), 0);
+        assert!(fs.get_path(&path("a/e/i")).is_none());
+
+        let options = ScanOptions {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        let err = FileSystem::scan(&scratch.0, &options).err().unwrap();
+        assert_eq!(err.to_string(), "max depth must be at least 1");
+
+        assert!(FileSystem::scan(scratch.0.join("b.txt"), &ScanOptions::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        let scratch = Scratch::new("symlinks");
+        std::os::unix::fs::symlink(scratch.0.join("a"), scratch.0.join("d/to_a")).unwrap();
+        std::os::unix::fs::symlink(&scratch.0, scratch.0.join("a/e/loop")).unwrap();
+
+        let scan = |symlinks| {
+            let options = ScanOptions {
+                symlinks,
+                ..Default::default()
+            };
+            FileSystem::scan(&scratch.0, &options).unwrap()
+        };
+        let skipped = scan(Symlinks::Skip);
+        assert_eq!(skipped.du(), 1234);
+        assert!(skipped.get_path(&path("d/to_a")).is_none());
+
+        let links = scan(Symlinks::Link);
+        assert!(!links.get_path(&path("d/to_a")).unwrap().is_dir());
+
+        let followed = scan(Symlinks::Follow);
+        assert_eq!(followed.get_path(&path("d/to_a")).unwrap().du(), 230);
+        assert!(followed.get_path(&path("a/e/loop")).is_none());
+        assert_eq!(followed.du(), 1464);
+    }
+}
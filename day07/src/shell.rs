@@ -0,0 +1,286 @@
+use crate::{format_path, render, resolve, FileSystem, Machine, Path};
+use anyhow::anyhow as e;
+use anyhow::Context;
+use std::io::prelude::*;
+
+const HELP: &str = "\
+cd [path]                   change directory, to / without a path
+ls [path]                   list a directory in the same format as the puzzle
+pwd                         print the current directory
+du [-h] [-S] [path]         size of every directory under path, -S for biggest first
+tree [-s] [path]            draw the filesystem under path, -s for directory sizes
+find [path] [-type f|d] [-size [+|-]n]
+                            list entries, bigger (+), smaller (-) or exactly n bytes
+mkdir <path>                create an empty directory
+touch <size> <path>         create a file or change its size
+rm [-r] <path>              remove a file, or a directory with -r
+exit                        leave the shell
+";
+
+/// An interactive shell over a reconstructed filesystem
+pub struct Shell {
+    pub machine: Machine,
+}
+
+impl Shell {
+    pub fn new(machine: Machine) -> Self {
+        Shell { machine }
+    }
+
+    pub fn prompt(&self) -> String {
+        format!("{} $ ", self.machine.pwd())
+    }
+
+    /// Run a single command line writing any output to `out`. Returns false once the shell
+    /// should exit.
+    pub fn execute(&mut self, line: &str, out: &mut impl Write) -> anyhow::Result<bool> {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        match args[..] {
+            [] => {}
+            ["exit"] | ["quit"] => return Ok(false),
+            ["help"] => write!(out, "{}", HELP)?,
+            ["pwd"] => writeln!(out, "{}", self.machine.pwd())?,
+            ["cd"] => self.machine.cd("/")?,
+            ["cd", dir] => self.machine.cd(dir)?,
+            ["ls"] => self.ls(".", out)?,
+            ["ls", path] => self.ls(path, out)?,
+            ["du", ref rest @ ..] => self.du(rest, out)?,
+            ["tree", ref rest @ ..] => self.tree(rest, out)?,
+            ["find", ref rest @ ..] => self.find(rest, out)?,
+            ["mkdir", path] => self.mkdir(path)?,
+            ["touch", size, path] => self.touch(size.parse()?, path)?,
+            ["rm", path] => self.rm(path, false)?,
+            ["rm", "-r", path] => self.rm(path, true)?,
+            _ => return Err(e!("unknown command: {} (try help)", line.trim())),
+        }
+        Ok(true)
+    }
+
+    /// Resolve a path typed by the user and look it up
+    fn lookup(&self, target: &str) -> anyhow::Result<(Path, &FileSystem)> {
+        let path = resolve(&self.machine.cwd, target)?;
+        let fs = self
+            .machine
+            .fs
+            .get_path(&path)
+            .with_context(|| format!("no such file or directory: {}", format_path(&path)))?;
+        Ok((path, fs))
+    }
+
+    fn ls(&self, target: &str, out: &mut impl Write) -> anyhow::Result<()> {
+        let (_, fs) = self.lookup(target)?;
+        let entries = match fs {
+            FileSystem::File(_, _) => vec![fs],
+            FileSystem::Dir(_, _) => fs.sorted_children(),
+        };
+        for entry in entries {
+            match entry {
+                FileSystem::Dir(name, _) => writeln!(out, "dir {}", name)?,
+                FileSystem::File(name, size) => writeln!(out, "{} {}", size, name)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn du(&self, args: &[&str], out: &mut impl Write) -> anyhow::Result<()> {
+        let mut target = ".";
+        let mut human = false;
+        let mut by_size = false;
+        for &arg in args {
+            match arg {
+                "-h" => human = true,
+                "-S" => by_size = true,
+                _ if arg.starts_with('-') => return Err(e!("usage: du [-h] [-S] [path]")),
+                _ => target = arg,
+            }
+        }
+        let (path, fs) = self.lookup(target)?;
+        render::du(fs, &path, human, by_size, out)?;
+        Ok(())
+    }
+
+    fn tree(&self, args: &[&str], out: &mut impl Write) -> anyhow::Result<()> {
+        let (sizes, target) = match args {
+            [] => (false, "."),
+            ["-s"] => (true, "."),
+            ["-s", target] => (true, *target),
+            [target] => (false, *target),
+            _ => return Err(e!("usage: tree [-s] [path]")),
+        };
+        let (_, fs) = self.lookup(target)?;
+        render::tree(fs, sizes, out)?;
+        Ok(())
+    }
+
+    fn find(&self, args: &[&str], out: &mut impl Write) -> anyhow::Result<()> {
+        let mut target = ".";
+        let mut kind = None;
+        let mut size: Option<(std::cmp::Ordering, usize)> = None;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "-type" => match args.next() {
+                    Some(&"f") => kind = Some(false),
+                    Some(&"d") => kind = Some(true),
+                    _ => return Err(e!("-type must be f or d")),
+                },
+                "-size" => {
+                    let value = args.next().context("-size requires a value")?;
+                    size = Some(match value.as_bytes().first() {
+                        Some(b'+') => (std::cmp::Ordering::Greater, value[1..].parse()?),
+                        Some(b'-') => (std::cmp::Ordering::Less, value[1..].parse()?),
+                        _ => (std::cmp::Ordering::Equal, value.parse()?),
+                    });
+                }
+                _ if arg.starts_with('-') => return Err(e!("unknown find option {}", arg)),
+                _ => target = arg,
+            }
+        }
+        let (mut path, fs) = self.lookup(target)?;
+        let mut found = Vec::new();
+        find_walk(fs, &mut path, &mut found);
+        found.sort();
+        for (path, is_dir, entry_size) in found {
+            if matches!(kind, Some(dir) if dir != is_dir) {
+                continue;
+            }
+            if let Some((ordering, limit)) = size {
+                if entry_size.cmp(&limit) != ordering {
+                    continue;
+                }
+            }
+            writeln!(out, "{}", format_path(&path))?;
+        }
+        Ok(())
+    }
+
+    /// Split a path typed by the user into its parent directory and the new entry's name
+    fn parent_and_name(&self, target: &str) -> anyhow::Result<(Path, String)> {
+        let mut path = resolve(&self.machine.cwd, target)?;
+        if self.machine.fs.get_path(&path).is_some() {
+            return Err(e!("{} already exists", format_path(&path)));
+        }
+        let name = path.pop().context("the root directory already exists")?;
+        Ok((path, name))
+    }
+
+    fn mkdir(&mut self, target: &str) -> anyhow::Result<()> {
+        let (parent, name) = self.parent_and_name(target)?;
+        self.machine.fs.insert(&parent, FileSystem::dir(&name))
+    }
+
+    fn touch(&mut self, size: usize, target: &str) -> anyhow::Result<()> {
+        let path = resolve(&self.machine.cwd, target)?;
+        match self.machine.fs.get_path_mut(&path) {
+            Some(FileSystem::File(_, file_size)) => {
+                *file_size = size;
+                Ok(())
+            }
+            Some(FileSystem::Dir(_, _)) => Err(e!("{} is a directory", format_path(&path))),
+            None => {
+                let (parent, name) = self.parent_and_name(target)?;
+                self.machine
+                    .fs
+                    .insert(&parent, FileSystem::file(&name, size))
+            }
+        }
+    }
+
+    fn rm(&mut self, target: &str, recursive: bool) -> anyhow::Result<()> {
+        let (path, fs) = self.lookup(target)?;
+        if fs.is_dir() && !recursive {
+            return Err(e!("{} is a directory, use rm -r", format_path(&path)));
+        }
+        if self.machine.cwd.starts_with(&path) {
+            return Err(e!(
+                "cannot remove {}, it contains the current directory",
+                format_path(&path)
+            ));
+        }
+        self.machine.fs.remove(&path)?;
+        Ok(())
+    }
+}
+
+/// Collect the path, kind and size of every entry below `fs`, returning the size of `fs`
+fn find_walk(fs: &FileSystem, path: &mut Path, found: &mut Vec<(Path, bool, usize)>) -> usize {
+    let size = match fs {
+        FileSystem::File(_, size) => *size,
+        FileSystem::Dir(_, children) => children
+            .values()
+            .map(|child| {
+                path.push(child.name().to_string());
+                let size = find_walk(child, path, found);
+                path.pop();
+                size
+            })
+            .sum(),
+    };
+    found.push((path.clone(), fs.is_dir(), size));
+    size
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(shell: &mut Shell, line: &str) -> String {
+        let mut out = Vec::new();
+        shell.execute(line, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn example() -> Shell {
+        let mut machine = crate::testing::example();
+        machine.cd("/").unwrap();
+        Shell::new(machine)
+    }
+
+    #[test]
+    fn test_browse() {
+        let mut shell = example();
+        assert_eq!(
+            run(&mut shell, "ls"),
+            "dir a\n14848514 b.txt\n8504156 c.dat\ndir d\n"
+        );
+        run(&mut shell, "cd a/e");
+        assert_eq!(run(&mut shell, "pwd"), "/a/e\n");
+        assert_eq!(run(&mut shell, "du .."), "584\t/a/e\n94853\t/a\n");
+        assert_eq!(
+            run(&mut shell, "du -h /"),
+            "584\t/a/e\n93K\t/a\n24M\t/d\n46M\t/\n"
+        );
+        assert_eq!(
+            run(&mut shell, "du -S -h /"),
+            "46M\t/\n24M\t/d\n93K\t/a\n584\t/a/e\n"
+        );
+        assert_eq!(
+            run(&mut shell, "tree /a"),
+            "- a (dir)\n  - e (dir)\n    - i (file, size=584)\n  - f (file, size=29116)\n  - g (file, size=2557)\n  - h.lst (file, size=62596)\n"
+        );
+        assert_eq!(
+            run(&mut shell, "find / -type d -size -100000"),
+            "/a\n/a/e\n"
+        );
+        assert_eq!(run(&mut shell, "find /d -size +8000000"), "/d\n/d/d.log\n");
+        assert!(shell.execute("cd /b.txt", &mut Vec::new()).is_err());
+        assert!(!shell.execute("exit", &mut Vec::new()).unwrap());
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut shell = example();
+        run(&mut shell, "mkdir /x");
+        run(&mut shell, "touch 100 /x/y");
+        run(&mut shell, "touch 50 x/y");
+        assert_eq!(run(&mut shell, "ls x"), "50 y\n");
+        assert!(shell.execute("mkdir x", &mut Vec::new()).is_err());
+        assert!(shell.execute("rm x", &mut Vec::new()).is_err());
+        run(&mut shell, "rm -r x");
+        run(&mut shell, "rm d/k");
+        assert_eq!(run(&mut shell, "du d"), "17719346\t/d\n");
+        run(&mut shell, "cd a");
+        assert!(shell.execute("rm -r /a", &mut Vec::new()).is_err());
+        assert!(shell.execute("touch 5 e", &mut Vec::new()).is_err());
+    }
+}
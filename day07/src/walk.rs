@@ -0,0 +1,195 @@
+//! Visit every entry of a [`FileSystem`] with the chance to change it.
+use crate::{format_path, FileSystem, Path};
+use anyhow::anyhow as e;
+
+/// When a directory is visited relative to what is inside it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Before its contents, so changes to it affect what is visited next
+    Pre,
+    /// After its contents, so their changes are already reflected in it
+    Post,
+}
+
+/// What to do once an entry has been visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    /// Do not visit the contents of this directory, which only makes a difference in pre-order
+    SkipChildren,
+    /// Delete the entry along with everything inside it
+    Remove,
+}
+
+impl FileSystem {
+    /// Call `f` with the path and a mutable reference of every entry, children in name order.
+    ///
+    /// Paths are relative to `self`, which is visited too with an empty path. Entries renamed
+    /// with [`FileSystem::set_name`] are filed under their new name once every sibling has been
+    /// visited, renaming onto a name that is already taken is an error that undoes the renames
+    /// in that directory. Errors returned by `f` stop the walk, keeping any changes made so far.
+    pub fn walk_mut<F>(&mut self, order: Order, mut f: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&Path, &mut FileSystem) -> anyhow::Result<Visit>,
+    {
+        match walk(self, &mut Vec::new(), order, &mut f)? {
+            Visit::Remove => Err(e!("cannot remove the top of the walk")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn walk<F>(fs: &mut FileSystem, path: &mut Path, order: Order, f: &mut F) -> anyhow::Result<Visit>
+where
+    F: FnMut(&Path, &mut FileSystem) -> anyhow::Result<Visit>,
+{
+    if order == Order::Pre {
+        match f(path, fs)? {
+            Visit::Continue => {}
+            visit => return Ok(visit),
+        }
+    }
+    if let Some(children) = fs.children_mut() {
+        let mut names: Vec<String> = children.keys().cloned().collect();
+        names.sort();
+        let mut renamed = Vec::new();
+        for name in names {
+            let child = children.get_mut(&name).expect("names were just listed");
+            path.push(name);
+            let visit = walk(child, path, order, f)?;
+            let name = path.pop().expect("pushed above");
+            if visit == Visit::Remove {
+                children.remove(&name);
+            } else if child.name() != name {
+                renamed.push(name);
+            }
+        }
+        let mut entries: Vec<FileSystem> = renamed
+            .iter()
+            .map(|name| {
+                children
+                    .remove(name)
+                    .expect("renamed entries are still there")
+            })
+            .collect();
+        let clash = entries.iter().enumerate().find(|(i, entry)| {
+            children.contains_key(entry.name())
+                || entries[..*i]
+                    .iter()
+                    .any(|other| other.name() == entry.name())
+        });
+        if let Some((_, entry)) = clash {
+            let err = e!(
+                "cannot rename to {}, it already exists in {}",
+                entry.name(),
+                format_path(path)
+            );
+            for (entry, name) in entries.iter_mut().zip(&renamed) {
+                entry.set_name(name);
+            }
+            for entry in entries {
+                children.insert(entry.name().to_string(), entry);
+            }
+            return Err(err);
+        }
+        for entry in entries {
+            children.insert(entry.name().to_string(), entry);
+        }
+    }
+    if order == Order::Post {
+        return f(path, fs);
+    }
+    Ok(Visit::Continue)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render;
+    use crate::testing::example;
+
+    #[test]
+    fn test_visit_order() {
+        let mut machine = example();
+        for (order, expected) in [
+            (Order::Pre, ["/", "/a", "/a/e", "/a/e/i", "/a/f"]),
+            (Order::Post, ["/a/e/i", "/a/e", "/a/f", "/a/g", "/a/h.lst"]),
+        ] {
+            let mut seen = Vec::new();
+            machine
+                .fs
+                .walk_mut(order, |path, _| {
+                    seen.push(format_path(path));
+                    Ok(Visit::Continue)
+                })
+                .unwrap();
+            assert_eq!(seen.len(), 14);
+            assert_eq!(seen[..5], expected);
+        }
+    }
+
+    #[test]
+    fn test_bulk_changes() {
+        let mut machine = example();
+        // delete every file over 60000 bytes under /a and /d
+        for dir in ["a", "d"] {
+            machine
+                .fs
+                .get_mut(dir)
+                .unwrap()
+                .walk_mut(Order::Pre, |_, fs| match fs {
+                    FileSystem::File(_, size) if *size > 60000 => Ok(Visit::Remove),
+                    _ => Ok(Visit::Continue),
+                })
+                .unwrap();
+        }
+        assert_eq!(machine.fs.du(), 14848514 + 8504156 + 29116 + 2557 + 584);
+
+        // turn .txt files into .log files, double every size and drop empty directories
+        machine
+            .fs
+            .walk_mut(Order::Post, |path, fs| {
+                if let FileSystem::File(name, size) = fs {
+                    *size *= 2;
+                    if let Some(stem) = name.strip_suffix(".txt") {
+                        let new_name = format!("{}.log", stem);
+                        fs.set_name(&new_name);
+                    }
+                } else if !path.is_empty() && fs.children().unwrap().is_empty() {
+                    return Ok(Visit::Remove);
+                }
+                Ok(Visit::Continue)
+            })
+            .unwrap();
+        let mut out = Vec::new();
+        render::tree(&machine.fs, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=1168)
+    - f (file, size=58232)
+    - g (file, size=5114)
+  - b.log (file, size=29697028)
+  - c.dat (file, size=17008312)
+"
+        );
+
+        // clashing renames fail without losing anything
+        let err = machine.fs.walk_mut(Order::Pre, |_, fs| {
+            if fs.name() == "c.dat" {
+                fs.set_name("b.log");
+            }
+            Ok(Visit::Continue)
+        });
+        assert!(err.is_err());
+        assert!(machine.fs.get("c.dat").is_some());
+        assert_eq!(machine.fs.du(), 29697028 + 17008312 + 58232 + 5114 + 1168);
+        assert!(machine
+            .fs
+            .walk_mut(Order::Pre, |_, _| Ok(Visit::Remove))
+            .is_err());
+    }
+}
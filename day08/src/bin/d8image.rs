@@ -0,0 +1,63 @@
+//! Draw the day08 forest as Netpbm images.
+//!
+//! Usage: `d8image [--scores file.pgm] [--visible file.ppm] [--scale linear|log] [--plain]
+//! [options]`, where the other options are those of the puzzle parts, see [`Options`].
+//!
+//! `--scores` writes a greyscale heatmap of scenic scores and `--visible` the trees visible from
+//! the edge in white with the tree with the best scenic score in red.
+use anyhow::anyhow as e;
+use anyhow::Context;
+use day08::netpbm::{self, Encoding, Scale};
+use day08::sight::Sight;
+use day08::{scenic, visibility, Options};
+use std::fs::File;
+use std::io::BufWriter;
+
+fn main() -> anyhow::Result<()> {
+    let mut rest = Vec::new();
+    let mut scores_file = None;
+    let mut visible_file = None;
+    let mut scale = Scale::default();
+    let mut encoding = Encoding::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| e!("{} requires a value", arg));
+        match arg.as_str() {
+            "--scores" => scores_file = Some(value()?),
+            "--visible" => visible_file = Some(value()?),
+            "--scale" => scale = value()?.parse()?,
+            "--plain" => encoding = Encoding::Plain,
+            _ => rest.push(arg),
+        }
+    }
+    if scores_file.is_none() && visible_file.is_none() {
+        return Err(e!("nothing to draw, pass --scores or --visible"));
+    }
+    let options = Options::from_args(rest.into_iter())?;
+    let forest = options.forest()?;
+    let sight = &options.sight;
+    if let Some(path) = scores_file {
+        let scores = if *sight == Sight::default() {
+            scenic::scores(&forest.trees)
+        } else {
+            sight.scores(&forest.trees)
+        };
+        let mut out = BufWriter::new(File::create(&path).with_context(|| path.clone())?);
+        netpbm::write_pgm(&netpbm::heatmap(&scores, scale), encoding, &mut out)?;
+    }
+    if let Some(path) = visible_file {
+        let (visible, best) = if *sight == Sight::default() {
+            let best = scenic::top(&forest.trees, 1)
+                .first()
+                .map(|&(coord, _)| coord);
+            (visibility::visible(&forest.trees), best)
+        } else {
+            let best = sight.top(&forest.trees, 1).first().map(|&(coord, _)| coord);
+            (sight.all_visible(&forest.trees), best)
+        };
+        let image = netpbm::visibility_map(&visible, best);
+        let mut out = BufWriter::new(File::create(&path).with_context(|| path.clone())?);
+        netpbm::write_ppm(&image, encoding, &mut out)?;
+    }
+    Ok(())
+}
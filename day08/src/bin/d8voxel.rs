@@ -0,0 +1,26 @@
+//! The day08 puzzle on a block of voxels.
+//!
+//! Usage: `d8voxel [--heights digits|separated] [input]`, where the input, `voxels.txt` by
+//! default, has layers of heights separated by blank lines.
+//! Prints how many voxels are visible from outside the block and the best scenic score.
+use anyhow::anyhow as e;
+use day08::parse::Heights;
+use day08::voxel::Voxels;
+
+fn main() -> anyhow::Result<()> {
+    let mut filename = "voxels.txt".to_string();
+    let mut heights = Heights::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| e!("{} requires a value", arg));
+        match arg.as_str() {
+            "--heights" => heights = value()?.parse()?,
+            _ => filename = arg,
+        }
+    }
+    let input = std::fs::read_to_string(&filename)?;
+    let voxels = Voxels::parse(&input, heights)?;
+    println!("{}", voxels.all_visible().len());
+    println!("{}", voxels.best_scenic_score());
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use anyhow::anyhow as e;
+use grid::{Coord, Direction, Grid};
+use parse::{Heights, ParseError};
+use sight::Sight;
+use std::collections::HashSet;
+
+pub mod netpbm;
+pub mod parse;
+pub mod scenic;
+pub mod sight;
+pub mod visibility;
+pub mod voxel;
+
+/// Tree heights with the top left tree at the root coord
+pub struct Forest {
+    pub trees: Grid<u8>,
+}
+
+impl Forest {
+    pub fn visible(&self, coord: Coord) -> bool {
+        self.visible_from(coord, Direction::N)
+            || self.visible_from(coord, Direction::S)
+            || self.visible_from(coord, Direction::E)
+            || self.visible_from(coord, Direction::W)
+    }
+
+    pub fn visible_from(&self, coord: Coord, from_direction: Direction) -> bool {
+        let mut walk = self.trees.walk(coord, from_direction.opposite());
+        let start_height = if let Some((_, height)) = walk.next() {
+            height
+        } else {
+            return false;
+        };
+
+        walk.all(|(_, height)| start_height > height)
+    }
+
+    /// The trees seen looking into the forest from the edge in `direction`, along column or row
+    /// `col_or_row`. Trees are only seen from the four edges, so diagonal directions, lines
+    /// outside the forest and an empty forest see nothing.
+    pub fn all_visible_from(&self, col_or_row: usize, direction: Direction) -> Vec<(Coord, u8)> {
+        let start = match direction {
+            Direction::N => Some((col_or_row, 0)),
+            Direction::S => self.height().checked_sub(1).map(|y| (col_or_row, y)),
+            Direction::E => self.width().checked_sub(1).map(|x| (x, col_or_row)),
+            Direction::W => Some((0, col_or_row)),
+            _ => None,
+        };
+        let start: Coord = match start {
+            Some(start) => start,
+            None => return Vec::new(),
+        };
+        let mut tallest = 0;
+        let mut output = Vec::new();
+        for (coord, &height) in self.trees.walk(start, direction.opposite()) {
+            if height > tallest || coord == start {
+                tallest = height;
+                output.push((coord, height))
+            }
+        }
+        output
+    }
+
+    /// Every tree visible from outside the forest, see [`visibility::visible`]
+    pub fn all_visible(&self) -> HashSet<(Coord, u8)> {
+        visibility::visible(&self.trees)
+            .iter()
+            .filter(|(_, &visible)| visible)
+            .map(|(coord, _)| (coord, self.trees[coord]))
+            .collect()
+    }
+
+    /// Read heights written as `heights`, see [`parse::parse`]
+    pub fn parse(s: &str, heights: Heights) -> Result<Self, ParseError> {
+        Ok(Forest {
+            trees: parse::parse(s, heights)?,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.trees.height()
+    }
+
+    pub fn width(&self) -> usize {
+        self.trees.width()
+    }
+
+    pub fn scenic_score(&self, coord: Coord) -> Option<usize> {
+        let house_height = *self.trees.get(coord)?;
+        let mut score = 1;
+        for dir in [Direction::N, Direction::S, Direction::E, Direction::W] {
+            let mut dir_score = 0;
+            for (_, &height) in self.trees.walk(coord, dir).skip(1) {
+                dir_score += 1;
+                if height >= house_height {
+                    break;
+                }
+            }
+            score *= dir_score;
+        }
+        Some(score)
+    }
+}
+
+impl TryFrom<&str> for Forest {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(Forest::parse(value, Heights::Digits)?)
+    }
+}
+
+/// Command line options shared by both parts
+pub struct Options {
+    pub filename: String,
+    pub heights: Heights,
+    pub sight: Sight,
+    /// How many of the best trees to list, part 2 only
+    pub top: Option<usize>,
+}
+
+impl Options {
+    /// Usage: `[--heights digits|separated] [--diagonal]
+    /// [--blocking at-least-as-tall|taller|nothing] [--max-distance n] [--top k] [filename]`
+    pub fn from_args(args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Options {
+            filename: "input.txt".to_string(),
+            heights: Heights::default(),
+            sight: Sight::default(),
+            top: None,
+        };
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| e!("{} requires a value", arg));
+            match arg.as_str() {
+                "--heights" => options.heights = value()?.parse()?,
+                "--diagonal" => options.sight = options.sight.diagonal(),
+                "--blocking" => options.sight.blocking = value()?.parse()?,
+                "--max-distance" => options.sight.max_distance = Some(value()?.parse()?),
+                "--top" => options.top = Some(value()?.parse()?),
+                _ => options.filename = arg,
+            }
+        }
+        Ok(options)
+    }
+
+    /// Read the forest from [`Options::filename`]
+    pub fn forest(&self) -> anyhow::Result<Forest> {
+        let input = std::fs::read_to_string(&self.filename)?;
+        Ok(Forest::parse(&input, self.heights)?)
+    }
+}
+
+/// Fixtures shared by the tests of every module
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// The forest from the puzzle's example
+    pub(crate) fn example() -> Forest {
+        Forest::try_from(include_str!("../example.txt")).unwrap()
+    }
+
+    /// Small forests with the shapes that trip up visibility and scenic scores: a single tree,
+    /// equal heights, ramps, walls around a pit, alternating heights and heights above 9
+    pub(crate) fn forests() -> Vec<Forest> {
+        let digits = [
+            "5",
+            "333\n333",
+            "3\n1\n4\n1\n5\n9\n2\n6",
+            "0123456789\n9876543210\n0123456789",
+            "11111\n19991\n19091\n19991\n11111",
+            "90909\n09090\n90909\n09090",
+            include_str!("../example.txt"),
+        ];
+        let mut forests: Vec<Forest> = digits
+            .iter()
+            .map(|s| Forest::try_from(*s).unwrap())
+            .collect();
+        forests.push(
+            Forest::parse(
+                "10 200 30 255\n0 200 200 7\n255 1 30 30",
+                Heights::Separated,
+            )
+            .unwrap(),
+        );
+        // bigger forests repeat a row of heights, shifted along by a few trees on every row
+        for (row, width, height, shift) in [("30373255121", 20, 20, 3), ("9081726354", 31, 17, 7)] {
+            let row: Vec<u8> = row.bytes().map(|b| b - b'0').collect();
+            let cells = (0..width * height)
+                .map(|i| row[(i % width + i / width * shift) % row.len()])
+                .collect();
+            forests.push(Forest {
+                trees: Grid::from_vec(width, cells).unwrap(),
+            });
+        }
+        forests
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+
+    #[test]
+    fn test_example() {
+        let grid = example();
+        let all_visible = grid.all_visible();
+        let mut sorted_all_visible: Vec<_> = all_visible.clone().into_iter().collect();
+        sorted_all_visible.sort();
+        println!("{:?}", all_visible);
+        let all_visible_count = grid.all_visible().len();
+        assert!(grid.visible((1, 0)));
+        assert!(grid.visible((1, 1)));
+        assert!(grid.visible((2, 1)));
+        assert!(grid.visible((3, 2)));
+
+        assert!(!grid.visible((3, 1)));
+        assert!(!grid.visible((2, 2)));
+
+        assert!(all_visible_count == 21, "{} != 21", all_visible_count);
+    }
+
+    #[test]
+    fn test_options() {
+        let args = "--heights separated --diagonal --max-distance 3 --top 5 trees.txt";
+        let options = Options::from_args(args.split(' ').map(String::from)).unwrap();
+        assert_eq!(options.filename, "trees.txt");
+        assert_eq!(options.heights, Heights::Separated);
+        assert_eq!(options.sight.directions, Direction::ALL);
+        assert_eq!(options.sight.max_distance, Some(3));
+        assert_eq!(options.top, Some(5));
+        assert!(Options::from_args(["--top".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_all_visible_from() {
+        let grid = example();
+        assert!(dbg!(grid.all_visible_from(1, Direction::E)) == vec![((4, 1), 2), ((2, 1), 5)]);
+        assert!(
+            dbg!(grid.all_visible_from(4, Direction::W))
+                == vec![((0, 4), 3), ((1, 4), 5), ((3, 4), 9)]
+        );
+        assert!(dbg!(grid.all_visible_from(0, Direction::S)) == vec![((0, 4), 3), ((0, 2), 6)]);
+        assert!(dbg!(grid.all_visible_from(4, Direction::N)) == vec![((4, 0), 3), ((4, 3), 9)]);
+        assert!(grid.all_visible_from(0, Direction::NE).is_empty());
+        assert!(grid.all_visible_from(5, Direction::N).is_empty());
+        let empty = Forest::try_from("").unwrap();
+        for direction in Direction::ALL {
+            assert!(empty.all_visible_from(0, direction).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_scenic_score() {
+        let grid = example();
+        assert!(dbg!(grid.scenic_score((2, 1))) == Some(4));
+        assert!(dbg!(grid.scenic_score((2, 3))) == Some(8));
+    }
+}
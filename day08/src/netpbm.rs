@@ -0,0 +1,184 @@
+//! Netpbm images of a forest, which most image viewers open without needing an image crate.
+use anyhow::anyhow as e;
+use grid::{Coord, Grid};
+use std::io::prelude::*;
+use std::str::FromStr;
+
+/// Netpbm images come as whitespace separated decimal numbers or as raw bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// `P2`/`P3`, readable and diffable
+    Plain,
+    /// `P5`/`P6`, one byte per sample
+    #[default]
+    Binary,
+}
+
+/// How scores are spread over the grey levels of a heatmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Logarithmic, so the handful of very high scores do not leave everything else black
+    Log,
+}
+
+impl FromStr for Scale {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Scale::Linear),
+            "log" => Ok(Scale::Log),
+            _ => Err(e!("scale must be linear or log, not {}", s)),
+        }
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+const VISIBLE: Rgb = [255, 255, 255];
+const HIDDEN: Rgb = [0, 0, 0];
+const HIGHLIGHT: Rgb = [255, 0, 0];
+
+/// Write a greyscale image with a pixel per cell, 0 black and 255 white
+pub fn write_pgm(grid: &Grid<u8>, encoding: Encoding, out: &mut impl Write) -> std::io::Result<()> {
+    let magic = match encoding {
+        Encoding::Plain => "P2",
+        Encoding::Binary => "P5",
+    };
+    write_header(magic, grid, out)?;
+    write_samples(grid.rows(), encoding, out)
+}
+
+/// Write a colour image with a pixel per cell
+pub fn write_ppm(
+    grid: &Grid<Rgb>,
+    encoding: Encoding,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let magic = match encoding {
+        Encoding::Plain => "P3",
+        Encoding::Binary => "P6",
+    };
+    write_header(magic, grid, out)?;
+    let rows: Vec<Vec<u8>> = grid.rows().map(|row| row.concat()).collect();
+    write_samples(rows.iter().map(|row| row.as_slice()), encoding, out)
+}
+
+fn write_header<T>(magic: &str, grid: &Grid<T>, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "{}", magic)?;
+    writeln!(out, "{} {}", grid.width(), grid.height())?;
+    writeln!(out, "255")
+}
+
+fn write_samples<'a>(
+    rows: impl Iterator<Item = &'a [u8]>,
+    encoding: Encoding,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    for row in rows {
+        match encoding {
+            Encoding::Plain => {
+                // plain lines should not be longer than 70 characters, which a row of up to 17
+                // samples of 3 digits each never is
+                for samples in row.chunks(17) {
+                    let samples: Vec<String> =
+                        samples.iter().map(|sample| sample.to_string()).collect();
+                    writeln!(out, "{}", samples.join(" "))?;
+                }
+            }
+            Encoding::Binary => out.write_all(row)?,
+        }
+    }
+    Ok(())
+}
+
+/// Scenic scores as grey levels, the best score in the grid white and 0 black
+pub fn heatmap(scores: &Grid<usize>, scale: Scale) -> Grid<u8> {
+    let best = scores
+        .iter()
+        .map(|(_, &score)| score)
+        .max()
+        .unwrap_or_default();
+    let level = |score: usize| match scale {
+        Scale::Linear => score as f64 / best as f64,
+        Scale::Log => (score as f64).ln_1p() / (best as f64).ln_1p(),
+    };
+    scores.map(|_, &score| {
+        if best == 0 {
+            0
+        } else {
+            (level(score) * 255.0).round() as u8
+        }
+    })
+}
+
+/// Trees visible from the edge in white and hidden ones in black, with `highlight` in red
+pub fn visibility_map(visible: &Grid<bool>, highlight: Option<Coord>) -> Grid<Rgb> {
+    visible.map(|coord, &visible| {
+        if highlight == Some(coord) {
+            HIGHLIGHT
+        } else if visible {
+            VISIBLE
+        } else {
+            HIDDEN
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+    use crate::{scenic, visibility};
+
+    #[test]
+    fn test_heatmap() {
+        let scores = scenic::scores(&example().trees);
+        let mut out = Vec::new();
+        write_pgm(&heatmap(&scores, Scale::Linear), Encoding::Plain, &mut out).unwrap();
+        // the best score of 8 is white, the edges scoring 0 are black and the rest in between
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+P2
+5 5
+255
+0 0 0 0 0
+0 32 128 32 0
+0 191 32 64 0
+0 32 255 96 0
+0 0 0 0 0
+"
+        );
+
+        let log = heatmap(&scores, Scale::Log);
+        assert_eq!(log[(2, 3)], 255);
+        assert!(log[(2, 1)] > 128);
+        assert!(heatmap(&Grid::new(2, 2, 0), Scale::Log)
+            .iter()
+            .all(|(_, &v)| v == 0));
+    }
+
+    #[test]
+    fn test_visibility_map() {
+        let trees = example().trees;
+        let image = visibility_map(&visibility::visible(&trees), Some((2, 3)));
+        let mut out = Vec::new();
+        write_ppm(&image, Encoding::Binary, &mut out).unwrap();
+        let header = b"P6\n5 5\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 5 * 5 * 3);
+        let pixel = |x: usize, y: usize| {
+            let i = header.len() + (y * 5 + x) * 3;
+            [out[i], out[i + 1], out[i + 2]]
+        };
+        assert_eq!(pixel(0, 0), VISIBLE);
+        assert_eq!(pixel(3, 1), HIDDEN);
+        assert_eq!(pixel(2, 3), HIGHLIGHT);
+
+        let mut out = Vec::new();
+        write_ppm(&Grid::new(1, 1, HIGHLIGHT), Encoding::Plain, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "P3\n1 1\n255\n255 0 0\n");
+    }
+}
@@ -0,0 +1,211 @@
+//! Reading grids of tree heights, with errors that say where the input went wrong.
+use anyhow::anyhow as e;
+use grid::Grid;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the heights on a line are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heights {
+    /// A single digit per tree with nothing in between, as in the puzzle
+    #[default]
+    Digits,
+    /// Numbers from 0 to 255 separated by whitespace or commas
+    Separated,
+}
+
+impl FromStr for Heights {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Heights::Digits),
+            "separated" => Ok(Heights::Separated),
+            _ => Err(e!("heights must be digits or separated, not {}", s)),
+        }
+    }
+}
+
+/// Where and why a grid of heights could not be read, lines and columns count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    NotAHeight {
+        line: usize,
+        column: usize,
+        value: String,
+        max: u8,
+    },
+    Ragged {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::NotAHeight {
+                line,
+                column,
+                value,
+                max,
+            } => write!(
+                f,
+                "line {} column {}: {:?} is not a height from 0 to {}",
+                line, column, value, max
+            ),
+            ParseError::Ragged {
+                line,
+                width,
+                expected,
+            } => write!(
+                f,
+                "line {} has {} heights but the first line has {}",
+                line, width, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Read one row of heights per line.
+///
+/// Trailing whitespace and blank lines at the end of the input are ignored, every other line
+/// must hold as many heights as the first.
+pub fn parse(s: &str, heights: Heights) -> Result<Grid<u8>, ParseError> {
+    let max = match heights {
+        Heights::Digits => 9,
+        Heights::Separated => u8::MAX,
+    };
+    let mut cells = Vec::new();
+    let mut width = None;
+    for (y, line) in s.trim_end().lines().enumerate() {
+        let values = match heights {
+            Heights::Digits => digits(line.trim_end()),
+            Heights::Separated => separated(line),
+        };
+        let mut row_width = 0;
+        for (column, value) in values {
+            let height = value
+                .parse()
+                .ok()
+                .filter(|&height| height <= max && value.chars().all(|c| c.is_ascii_digit()))
+                .ok_or_else(|| ParseError::NotAHeight {
+                    line: y + 1,
+                    column,
+                    value: value.to_string(),
+                    max,
+                })?;
+            cells.push(height);
+            row_width += 1;
+        }
+        match width {
+            None => width = Some(row_width),
+            Some(expected) if expected != row_width => {
+                return Err(ParseError::Ragged {
+                    line: y + 1,
+                    width: row_width,
+                    expected,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(Grid::from_vec(width.unwrap_or_default(), cells).expect("every row has the same width"))
+}
+
+/// Every character with its column
+fn digits(line: &str) -> Vec<(usize, &str)> {
+    line.char_indices()
+        .enumerate()
+        .map(|(column, (i, c))| (column + 1, &line[i..i + c.len_utf8()]))
+        .collect()
+}
+
+/// Every run of characters between whitespace and commas with the column it starts at
+fn separated(line: &str) -> Vec<(usize, &str)> {
+    let is_separator = |c: char| c.is_whitespace() || c == ',';
+    let mut start = None;
+    let mut values = Vec::new();
+    for (column, (i, c)) in line.char_indices().chain([(line.len(), ' ')]).enumerate() {
+        match (start, is_separator(c)) {
+            (None, false) => start = Some((column + 1, i)),
+            (Some((column, from)), true) => {
+                values.push((column, &line[from..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        let grid = parse(include_str!("../example.txt"), Heights::Digits).unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 5));
+        assert_eq!(
+            parse("12\n34\n\n  \n", Heights::Digits).unwrap().height(),
+            2
+        );
+        assert_eq!(parse("", Heights::Digits).unwrap().width(), 0);
+
+        let err = parse("123\n4x6\n", Heights::Digits).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::NotAHeight {
+                line: 2,
+                column: 2,
+                value: "x".to_string(),
+                max: 9
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2 column 2: \"x\" is not a height from 0 to 9"
+        );
+        let err = parse("123\n45\n789\n", Heights::Digits).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::Ragged {
+                line: 2,
+                width: 2,
+                expected: 3
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2 has 2 heights but the first line has 3"
+        );
+        // a blank line in the middle is a row with no heights
+        assert!(parse("12\n\n34\n", Heights::Digits).is_err());
+    }
+
+    #[test]
+    fn test_separated() {
+        let grid = parse("10, 2 ,255\n 0 0\t7\n", Heights::Separated).unwrap();
+        assert_eq!(grid.row(0).copied().collect::<Vec<_>>(), [10, 2, 255]);
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [0, 0, 7]);
+
+        for (input, column, value) in [("1 256", 3, "256"), ("1,,-2", 4, "-2"), ("1 +2", 3, "+2")] {
+            assert_eq!(
+                parse(input, Heights::Separated).unwrap_err(),
+                ParseError::NotAHeight {
+                    line: 1,
+                    column,
+                    value: value.to_string(),
+                    max: 255
+                }
+            );
+        }
+        assert!(matches!(
+            parse("1 2\n3\n", Heights::Separated),
+            Err(ParseError::Ragged { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+//! Scenic scores for every tree at once.
+use grid::{Coord, Grid};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// How many trees can be seen from a tree house in each direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Views {
+    pub n: usize,
+    pub e: usize,
+    pub s: usize,
+    pub w: usize,
+}
+
+impl Views {
+    pub fn score(&self) -> usize {
+        self.n * self.e * self.s * self.w
+    }
+}
+
+/// The view distances from every tree.
+///
+/// Sweeping along a line with a stack of trees that are still tall enough to block the view
+/// gives every tree its view distance back along the line in amortised constant time, so
+/// four sweeps over the grid give all the views in time proportional to its size.
+pub fn views<T: Ord>(grid: &Grid<T>) -> Grid<Views> {
+    let mut views = grid.map(|_, _| Views::default());
+    let (width, height) = (grid.width(), grid.height());
+    for y in 0..height {
+        let row: Vec<Coord> = (0..width).map(|x| (x, y)).collect();
+        sweep(grid, &mut views, row.iter(), |views, d| views.w = d);
+        sweep(grid, &mut views, row.iter().rev(), |views, d| views.e = d);
+    }
+    for x in 0..width {
+        let column: Vec<Coord> = (0..height).map(|y| (x, y)).collect();
+        sweep(grid, &mut views, column.iter(), |views, d| views.n = d);
+        sweep(grid, &mut views, column.iter().rev(), |views, d| {
+            views.s = d
+        });
+    }
+    views
+}
+
+/// Set how far back along `line` every tree can see
+fn sweep<'a, T: Ord>(
+    grid: &Grid<T>,
+    views: &mut Grid<Views>,
+    line: impl Iterator<Item = &'a Coord>,
+    mut set: impl FnMut(&mut Views, usize),
+) {
+    // positions along the line of the trees that could still block a later view, their heights
+    // are strictly decreasing from the bottom of the stack
+    let mut blockers: Vec<(usize, &T)> = Vec::new();
+    for (i, &coord) in line.enumerate() {
+        let tree = &grid[coord];
+        while matches!(blockers.last(), Some(&(_, blocker)) if blocker < tree) {
+            blockers.pop();
+        }
+        let distance = match blockers.last() {
+            Some(&(j, _)) => i - j,
+            None => i,
+        };
+        set(&mut views[coord], distance);
+        blockers.push((i, tree));
+    }
+}
+
+/// The scenic score of every tree
+pub fn scores<T: Ord>(grid: &Grid<T>) -> Grid<usize> {
+    views(grid).map(|_, views| views.score())
+}
+
+/// The `k` trees with the best scenic scores, best first, ties going to the tree nearest the
+/// top and then the left
+pub fn top<T: Ord>(grid: &Grid<T>, k: usize) -> Vec<(Coord, Views)> {
+    let views = views(grid);
+    // a min heap of the best seen so far so the worst of them is cheap to drop
+    let mut best = BinaryHeap::new();
+    for ((x, y), views) in views.iter() {
+        best.push((Reverse(views.score()), (y, x)));
+        if best.len() > k {
+            best.pop();
+        }
+    }
+    best.into_sorted_vec()
+        .into_iter()
+        .map(|(_, (y, x))| ((x, y), views[(x, y)]))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{example, forests};
+
+    #[test]
+    fn test_example() {
+        let forest = example();
+        let views = views(&forest.trees);
+        assert_eq!(
+            views[(2, 1)],
+            Views {
+                n: 1,
+                e: 2,
+                s: 2,
+                w: 1
+            }
+        );
+        assert_eq!(
+            views[(2, 3)],
+            Views {
+                n: 2,
+                e: 2,
+                s: 1,
+                w: 2
+            }
+        );
+        assert_eq!(
+            top(&forest.trees, 2),
+            [
+                (
+                    (2, 3),
+                    Views {
+                        n: 2,
+                        e: 2,
+                        s: 1,
+                        w: 2
+                    }
+                ),
+                (
+                    (1, 2),
+                    Views {
+                        n: 1,
+                        e: 3,
+                        s: 2,
+                        w: 1
+                    }
+                ),
+            ]
+        );
+        assert_eq!(top(&forest.trees, 100).len(), 25);
+        assert!(top(&forest.trees, 0).is_empty());
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for forest in forests() {
+            for (coord, &score) in scores(&forest.trees).iter() {
+                assert_eq!(Some(score), forest.scenic_score(coord), "{:?}", coord);
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Configurable lines of sight, for visibility and scenic scores under other rules than the
+//! puzzle's.
+use anyhow::anyhow as e;
+use grid::{Coord, Direction, Grid};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// Which trees along a line of sight stop it, compared to the tree being looked from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blocking {
+    /// Trees at least as tall, as in the puzzle
+    #[default]
+    AtLeastAsTall,
+    /// Only strictly taller trees, trees of the same height can be seen past
+    Taller,
+    /// Nothing, the view only ends at the edge or the maximum distance
+    Nothing,
+}
+
+impl FromStr for Blocking {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "at-least-as-tall" => Ok(Blocking::AtLeastAsTall),
+            "taller" => Ok(Blocking::Taller),
+            "nothing" => Ok(Blocking::Nothing),
+            _ => Err(e!(
+                "blocking must be at-least-as-tall, taller or nothing, not {}",
+                s
+            )),
+        }
+    }
+}
+
+/// How far a tree can see in one direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct View {
+    /// Trees seen, including the one that blocked the view
+    pub distance: usize,
+    /// Whether the view reached the edge without being blocked or running out of distance, so
+    /// the tree can be seen from outside the forest in this direction
+    pub clear: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sight {
+    /// Directions looked in, scenic scores multiply the view distances in all of them
+    pub directions: Vec<Direction>,
+    pub blocking: Blocking,
+    /// No tree further than this many steps away can be seen
+    pub max_distance: Option<usize>,
+}
+
+/// The puzzle's rules, looking north, east, south and west
+impl Default for Sight {
+    fn default() -> Self {
+        Sight {
+            directions: Direction::ORTHOGONAL.to_vec(),
+            blocking: Blocking::default(),
+            max_distance: None,
+        }
+    }
+}
+
+impl Sight {
+    /// Look in all eight directions, diagonals included
+    pub fn diagonal(mut self) -> Self {
+        self.directions = Direction::ALL.to_vec();
+        self
+    }
+
+    fn blocks<T: Ord>(&self, from: &T, tree: &T) -> bool {
+        match self.blocking {
+            Blocking::AtLeastAsTall => tree >= from,
+            Blocking::Taller => tree > from,
+            Blocking::Nothing => false,
+        }
+    }
+
+    /// Look from `coord` in `direction`, `None` when `coord` is outside the grid
+    pub fn view<T: Ord>(&self, grid: &Grid<T>, coord: Coord, direction: Direction) -> Option<View> {
+        let from = grid.get(coord)?;
+        let mut distance = 0;
+        for (_, tree) in grid.walk(coord, direction).skip(1) {
+            if matches!(self.max_distance, Some(max) if distance == max) {
+                return Some(View {
+                    distance,
+                    clear: false,
+                });
+            }
+            distance += 1;
+            if self.blocks(from, tree) {
+                return Some(View {
+                    distance,
+                    clear: false,
+                });
+            }
+        }
+        Some(View {
+            distance,
+            clear: true,
+        })
+    }
+
+    /// The views from `coord` in every direction, in the order of [`Sight::directions`]
+    pub fn views<T: Ord>(&self, grid: &Grid<T>, coord: Coord) -> Option<Vec<View>> {
+        self.directions
+            .iter()
+            .map(|&direction| self.view(grid, coord, direction))
+            .collect()
+    }
+
+    /// Whether the tree at `coord` can be seen from outside the forest in any direction
+    pub fn visible<T: Ord>(&self, grid: &Grid<T>, coord: Coord) -> bool {
+        matches!(self.views(grid, coord), Some(views) if views.iter().any(|view| view.clear))
+    }
+
+    pub fn scenic_score<T: Ord>(&self, grid: &Grid<T>, coord: Coord) -> Option<usize> {
+        Some(
+            self.views(grid, coord)?
+                .iter()
+                .map(|view| view.distance)
+                .product(),
+        )
+    }
+
+    /// [`Sight::visible`] for every tree.
+    ///
+    /// Every tree walks every direction to the first blocker, so this is slower than
+    /// [`crate::visibility::visible`] which only handles the default rules.
+    pub fn all_visible<T: Ord>(&self, grid: &Grid<T>) -> Grid<bool> {
+        grid.map(|coord, _| self.visible(grid, coord))
+    }
+
+    /// [`Sight::scenic_score`] for every tree, with the same caveat as [`Sight::all_visible`]
+    /// compared to [`crate::scenic::scores`]
+    pub fn scores<T: Ord>(&self, grid: &Grid<T>) -> Grid<usize> {
+        grid.map(|coord, _| {
+            self.scenic_score(grid, coord)
+                .expect("coordinates come from the grid")
+        })
+    }
+
+    /// The `k` trees with the best scenic scores and their views, ordered like
+    /// [`crate::scenic::top`]
+    pub fn top<T: Ord>(&self, grid: &Grid<T>, k: usize) -> Vec<(Coord, Vec<View>)> {
+        let mut best = BinaryHeap::new();
+        for ((x, y), score) in self.scores(grid).iter() {
+            best.push((Reverse(*score), (y, x)));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|(_, (y, x))| {
+                let views = self
+                    .views(grid, (x, y))
+                    .expect("coordinates come from the grid");
+                ((x, y), views)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::example;
+    use crate::{scenic, visibility};
+
+    fn count(seen: &Grid<bool>) -> usize {
+        seen.iter().filter(|(_, &v)| v).count()
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let forest = example();
+        let sight = Sight::default();
+        assert_eq!(
+            sight.all_visible(&forest.trees),
+            visibility::visible(&forest.trees)
+        );
+        assert_eq!(sight.scores(&forest.trees), scenic::scores(&forest.trees));
+        assert_eq!(
+            sight.view(&forest.trees, (2, 3), Direction::N),
+            Some(View {
+                distance: 2,
+                clear: false
+            })
+        );
+        assert_eq!(sight.view(&forest.trees, (5, 0), Direction::N), None);
+    }
+
+    #[test]
+    fn test_other_rules() {
+        let forest = example();
+        let trees = &forest.trees;
+
+        // the 5 in the middle of the second row sees past the 5 to its east once equal
+        // heights stop blocking, and then is visible from the east edge too
+        let taller = Sight {
+            blocking: Blocking::Taller,
+            ..Sight::default()
+        };
+        let e = taller.view(trees, (1, 1), Direction::E).unwrap();
+        assert_eq!(
+            e,
+            View {
+                distance: 3,
+                clear: true
+            }
+        );
+        // the 3 in the middle and the second 3 in the fourth row join the 21 visible trees
+        assert_eq!(count(&taller.all_visible(trees)), 23);
+        assert!(taller.visible(trees, (2, 2)));
+        assert!(taller.visible(trees, (1, 3)));
+
+        let nothing = Sight {
+            blocking: Blocking::Nothing,
+            ..Sight::default()
+        };
+        assert_eq!(count(&nothing.all_visible(trees)), 25);
+        assert_eq!(nothing.scenic_score(trees, (2, 2)), Some(16));
+
+        let near = Sight {
+            blocking: Blocking::Nothing,
+            max_distance: Some(1),
+            ..Sight::default()
+        };
+        assert_eq!(near.scenic_score(trees, (2, 2)), Some(1));
+        // only the middle tree is two or more steps from every edge
+        assert_eq!(count(&near.all_visible(trees)), 24);
+
+        // the 3 in the middle sees past the 1 to its north east to the 3 in the corner, every
+        // other direction is blocked by its neighbour
+        let diagonal = Sight::default().diagonal();
+        let views = diagonal.views(trees, (2, 2)).unwrap();
+        let distances: Vec<usize> = views.iter().map(|view| view.distance).collect();
+        assert_eq!(distances, [1, 2, 1, 1, 1, 1, 1, 1]);
+        assert!(views.iter().all(|view| !view.clear));
+        assert_eq!(diagonal.scenic_score(trees, (2, 2)), Some(2));
+        // the 4 in the fourth row can be seen past the 2 to its north east
+        assert!(diagonal.visible(trees, (3, 3)));
+        assert!(!Sight::default().visible(trees, (3, 3)));
+        assert_eq!(count(&diagonal.all_visible(trees)), 22);
+    }
+
+    #[test]
+    fn test_top() {
+        let trees = example().trees;
+        let sight = Sight::default();
+        let top = sight.top(&trees, 3);
+        let expected = scenic::top(&trees, 3);
+        assert_eq!(top.len(), 3);
+        for ((coord, views), (expected_coord, expected_views)) in top.iter().zip(expected) {
+            assert_eq!(*coord, expected_coord);
+            let distances: Vec<usize> = views.iter().map(|view| view.distance).collect();
+            let expected = expected_views;
+            assert_eq!(distances, [expected.n, expected.e, expected.s, expected.w]);
+        }
+
+        // with nothing blocking the middle tree sees furthest, and of the two trees next to it
+        // that score 12 the one nearer the top comes first
+        let nothing = Sight {
+            blocking: Blocking::Nothing,
+            ..Sight::default()
+        };
+        let top = nothing.top(&trees, 2);
+        assert_eq!(top[0].0, (2, 2));
+        assert_eq!(top[1].0, (2, 1));
+        assert!(nothing.top(&trees, 0).is_empty());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("taller".parse::<Blocking>().unwrap(), Blocking::Taller);
+        assert!("shorter".parse::<Blocking>().is_err());
+    }
+}
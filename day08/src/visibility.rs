@@ -0,0 +1,70 @@
+//! Which trees can be seen from outside the forest, in a single pass per edge.
+use grid::Grid;
+
+/// Mark every cell strictly taller than everything between it and at least one edge.
+///
+/// Each row is swept from the west and the east and each column from the north and the south,
+/// remembering the tallest cell so far, so this takes time proportional to the number of cells
+/// for any ordered heights.
+pub fn visible<T: Ord>(grid: &Grid<T>) -> Grid<bool> {
+    let mut seen = grid.map(|_, _| false);
+    let (width, height) = (grid.width(), grid.height());
+    let rows = (0..height).map(|y| (0..width).map(move |x| (x, y)).collect::<Vec<_>>());
+    let columns = (0..width).map(|x| (0..height).map(move |y| (x, y)).collect::<Vec<_>>());
+    for line in rows.chain(columns) {
+        sweep(grid, &mut seen, line.iter());
+        sweep(grid, &mut seen, line.iter().rev());
+    }
+    seen
+}
+
+fn sweep<'a, T: Ord>(
+    grid: &Grid<T>,
+    seen: &mut Grid<bool>,
+    line: impl Iterator<Item = &'a grid::Coord>,
+) {
+    let mut tallest: Option<&T> = None;
+    for &coord in line {
+        let tree = &grid[coord];
+        if !matches!(tallest, Some(tallest) if tree <= tallest) {
+            seen[coord] = true;
+            tallest = Some(tree);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{example, forests};
+
+    #[test]
+    fn test_example() {
+        let forest = example();
+        let seen = visible(&forest.trees);
+        assert_eq!(seen.iter().filter(|(_, &v)| v).count(), 21);
+        assert!(!seen[(3, 1)]);
+        assert!(seen[(3, 2)]);
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for forest in forests() {
+            let seen = visible(&forest.trees);
+            for (coord, &v) in seen.iter() {
+                assert_eq!(
+                    v,
+                    forest.visible(coord),
+                    "{:?} in\n{:?}",
+                    coord,
+                    forest.trees
+                );
+            }
+        }
+        // heights are not limited to single digits or even to numbers
+        let words = Grid::from_vec(3, vec!["b", "c", "b", "c", "bb", "c", "b", "c", "b"]).unwrap();
+        let seen = visible(&words);
+        assert_eq!(seen.iter().filter(|(_, &v)| !v).count(), 1);
+        assert!(!seen[(1, 1)]);
+    }
+}
@@ -0,0 +1,229 @@
+//! The tree house problem in three dimensions, with layers of height digits stacked on top of
+//! each other.
+use crate::parse::{parse, Heights};
+use anyhow::anyhow as e;
+use grid::{signed_add, Grid};
+
+/// `(x, y, z)` with `z` counting layers from the first one in the input
+pub type Point = (usize, usize, usize);
+
+/// One step along each axis, the four grid directions then down and up the layers
+pub const FACES: [(isize, isize, isize); 6] = [
+    (0, -1, 0),
+    (1, 0, 0),
+    (0, 1, 0),
+    (-1, 0, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+/// Layers of heights, all with the same width and height
+pub struct Voxels {
+    pub layers: Vec<Grid<u8>>,
+}
+
+impl Voxels {
+    pub fn width(&self) -> usize {
+        self.layers
+            .first()
+            .map(|layer| layer.width())
+            .unwrap_or_default()
+    }
+
+    pub fn height(&self) -> usize {
+        self.layers
+            .first()
+            .map(|layer| layer.height())
+            .unwrap_or_default()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn get(&self, (x, y, z): Point) -> Option<u8> {
+        self.layers.get(z)?.get((x, y)).copied()
+    }
+
+    /// Every point, layer by layer and then row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(z, layer)| layer.coords().map(move |(x, y)| (x, y, z)))
+    }
+
+    /// The heights from `point`, not included, to the face of the block in direction `delta`
+    fn walk(&self, point: Point, delta: (isize, isize, isize)) -> impl Iterator<Item = u8> + '_ {
+        std::iter::successors(Some(point), move |&(x, y, z)| {
+            Some((
+                signed_add(x, delta.0)?,
+                signed_add(y, delta.1)?,
+                signed_add(z, delta.2)?,
+            ))
+        })
+        .skip(1)
+        .map_while(|point| self.get(point))
+    }
+
+    /// Whether the voxel at `point` is taller than everything between it and any of the six
+    /// faces of the block
+    pub fn visible(&self, point: Point) -> bool {
+        match self.get(point) {
+            Some(height) => FACES
+                .iter()
+                .any(|&delta| self.walk(point, delta).all(|other| other < height)),
+            None => false,
+        }
+    }
+
+    /// The product of how many voxels can be seen from `point` towards each face, stopping at the
+    /// first that is at least as tall
+    pub fn scenic_score(&self, point: Point) -> Option<usize> {
+        let height = self.get(point)?;
+        let mut score = 1;
+        for delta in FACES {
+            let mut distance = 0;
+            for other in self.walk(point, delta) {
+                distance += 1;
+                if other >= height {
+                    break;
+                }
+            }
+            score *= distance;
+        }
+        Some(score)
+    }
+
+    pub fn all_visible(&self) -> Vec<Point> {
+        self.points().filter(|&point| self.visible(point)).collect()
+    }
+
+    /// The highest scenic score of any voxel, 0 for an empty block
+    pub fn best_scenic_score(&self) -> usize {
+        self.points()
+            .filter_map(|point| self.scenic_score(point))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Voxels {
+    /// Layers of heights written as `heights`, separated by blank lines, see [`parse`]
+    pub fn parse(value: &str, heights: Heights) -> anyhow::Result<Self> {
+        let mut blocks: Vec<Vec<&str>> = Vec::new();
+        let mut in_block = false;
+        for line in value.lines() {
+            if line.trim().is_empty() {
+                in_block = false;
+            } else if in_block {
+                blocks.last_mut().expect("a block was started").push(line);
+            } else {
+                blocks.push(vec![line]);
+                in_block = true;
+            }
+        }
+        let mut layers: Vec<Grid<u8>> = Vec::new();
+        for (z, block) in blocks.iter().enumerate() {
+            let layer =
+                parse(&block.join("\n"), heights).map_err(|err| e!("layer {}: {}", z + 1, err))?;
+            if let Some(first) = layers.first() {
+                if (layer.width(), layer.height()) != (first.width(), first.height()) {
+                    return Err(e!(
+                        "layer {} is {}x{} but the first layer is {}x{}",
+                        z + 1,
+                        layer.width(),
+                        layer.height(),
+                        first.width(),
+                        first.height()
+                    ));
+                }
+            }
+            layers.push(layer);
+        }
+        Ok(Voxels { layers })
+    }
+}
+
+/// Layers in the day08 digit format
+impl TryFrom<&str> for Voxels {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Voxels::parse(value, Heights::Digits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CUBE: &str = "\
+111
+111
+111
+
+111
+121
+111
+
+111
+111
+111
+";
+
+    #[test]
+    fn test_cube() {
+        let voxels = Voxels::try_from(CUBE).unwrap();
+        assert_eq!((voxels.width(), voxels.height(), voxels.depth()), (3, 3, 3));
+        // the middle is taller than its neighbours, so every voxel can be seen from outside
+        assert_eq!(voxels.all_visible().len(), 27);
+        assert_eq!(voxels.scenic_score((1, 1, 1)), Some(1));
+        assert_eq!(voxels.scenic_score((0, 1, 1)), Some(0));
+
+        let hidden = Voxels::try_from(CUBE.replace('2', "0").as_str()).unwrap();
+        assert_eq!(hidden.all_visible().len(), 26);
+        assert!(!hidden.visible((1, 1, 1)));
+        assert_eq!(hidden.get((3, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_single_layer_matches_forest() {
+        let example = include_str!("../example.txt");
+        let forest = crate::Forest::try_from(example).unwrap();
+        let voxels = Voxels::try_from(format!("{0}\n{0}\n{0}", example).as_str()).unwrap();
+        assert_eq!(voxels.depth(), 3);
+        for (x, y) in forest.trees.coords() {
+            // sandwiched between two identical layers, the view up and down is always 1
+            assert_eq!(voxels.visible((x, y, 1)), forest.visible((x, y)));
+            assert_eq!(voxels.scenic_score((x, y, 1)), forest.scenic_score((x, y)));
+        }
+    }
+
+    #[test]
+    fn test_fixture() {
+        let voxels = Voxels::try_from(include_str!("../voxels.txt")).unwrap();
+        assert_eq!((voxels.width(), voxels.height(), voxels.depth()), (5, 5, 5));
+        assert_eq!(voxels.all_visible().len(), 114);
+        // the 9 in the middle of the second layer sees 2 voxels in every direction but down
+        assert_eq!(voxels.best_scenic_score(), 32);
+        assert_eq!(voxels.scenic_score((2, 2, 1)), Some(32));
+        assert!(!voxels.visible((2, 2, 2)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Voxels::try_from("12\n34\n\n12\n3x\n").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "layer 2: line 2 column 2: \"x\" is not a height from 0 to 9"
+        );
+        let err = Voxels::try_from("12\n34\n\n\n123\n").err().unwrap();
+        assert_eq!(err.to_string(), "layer 2 is 3x1 but the first layer is 2x2");
+        assert_eq!(Voxels::try_from("\n\n").unwrap().depth(), 0);
+
+        let voxels = Voxels::parse("10 20\n\n30 255\n", Heights::Separated).unwrap();
+        assert_eq!(voxels.get((0, 0, 1)), Some(30));
+        assert_eq!(voxels.best_scenic_score(), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Context;
+use grid::{Coord, Grid};
 //use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -10,10 +11,8 @@ use std::str::FromStr;
 //use std::thread::sleep;
 //use std::time::Duration;
 
-type Coord = (usize, usize);
-
 struct Map {
-    elevations: Vec<Vec<u8>>,
+    elevations: Grid<u8>,
     start: Coord,
     end: Coord,
 }
@@ -26,7 +25,7 @@ enum BFSResult {
 
 impl Map {
     fn get(&self, coord: Coord) -> Option<u8> {
-        self.elevations.get(coord.1)?.get(coord.0).copied()
+        self.elevations.get(coord).copied()
     }
 
     fn get_char(&self, coord: Coord) -> Option<char> {
@@ -38,26 +37,9 @@ impl Map {
         self.get(coord).map(|h| (h + 97) as char)
     }
 
-    /// Returns list of candidates for each node sorted by .heuristic()
+    /// Returns list of candidates for each node
     fn candidates(&self, node: Coord) -> Vec<Coord> {
-        let height = match self.get(node) {
-            Some(h) => h,
-            None => return Vec::new(),
-        };
-        let candidates: Vec<Coord> = [
-            (node.0.checked_sub(1), Some(node.1)),
-            (Some(node.0), node.1.checked_sub(1)),
-            (Some(node.0), Some(node.1 + 1)),
-            (Some(node.0 + 1), Some(node.1)),
-        ]
-        .into_iter()
-        .flat_map(|coord| match coord {
-            (Some(x), Some(y)) => Some((x, y)),
-            _ => None,
-        })
-        .collect();
-        //candidates.sort_by_key(|node| self.heuristic(*node));
-        candidates
+        self.elevations.neighbours(node).collect()
     }
 
     fn _bfs(
@@ -81,9 +63,7 @@ impl Map {
         let candidates = self
             .candidates(last_node)
             .into_iter()
-            .filter(|next_node| {
-                check(&self, last_node, *next_node)
-            })
+            .filter(|next_node| check(self, last_node, *next_node))
             .filter_map(|n| {
                 if visited.contains(&n) {
                     None
@@ -114,31 +94,12 @@ impl Map {
         None
     }
 
-    fn descend(&self) -> Option<Vec<Coord>> {
-        let mut visited = HashSet::new();
-        let mut candidates = VecDeque::from([Vec::from([self.start])]);
-        while let Some(path) = candidates.pop_front() {
-            match self._bfs(&mut visited, path) {
-                BFSResult::Complete(solution) => return Some(solution),
-                BFSResult::Continue(new_candidates) => {
-                    candidates.extend(new_candidates);
-                }
-            }
-            //dbg!(candidates.len(), visited.len());
-        }
-        None
-
-    }
-
     fn height(&self) -> usize {
-        self.elevations.len()
+        self.elevations.height()
     }
 
     fn width(&self) -> usize {
-        self.elevations
-            .first()
-            .map(|row| row.len())
-            .unwrap_or_default()
+        self.elevations.width()
     }
 
     fn can_climb(&self, from: Coord, to: Coord) -> bool {
@@ -147,13 +108,6 @@ impl Map {
             _ => false,
         }
     }
-
-    fn can_descend(&self, from: Coord, to: Coord) -> bool {
-        match (self.get(from), self.get(to)) {
-            (Some(from_height), Some(to_height)) => from_height.saturating_sub(to_height) <= 1,
-            _ => false,
-        }
-    }
 }
 
 // Calculate minimum distance between nodes given we can only travel vertically and horicontally.
@@ -166,7 +120,7 @@ fn distance(a: Coord, b: Coord) -> usize {
 fn height(c: char) -> u8 {
     match c {
         'a'..='z' => (c as u8) - 97,
-        'E' => 26,
+        'E' => 25,
         _ => 0,
     }
 }
@@ -197,18 +151,16 @@ fn print_path2(map: &Map, path: &[Coord]) -> anyhow::Result<()> {
 impl FromStr for Map {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut elevations = Vec::new();
         let mut start = None;
         let mut end = None;
-        for (i, line) in s.lines().enumerate() {
-            elevations.push(line.chars().map(height).collect());
-            if let Some(startx) = line.find('S') {
-                start = Some((startx, i));
-            }
-            if let Some(endx) = line.find('E') {
-                end = Some((endx, i));
+        let elevations = Grid::parse(s, |coord, c| {
+            match c {
+                'S' => start = Some(coord),
+                'E' => end = Some(coord),
+                _ => {}
             }
-        }
+            Ok(height(c))
+        })?;
         Ok(Self {
             elevations,
             start: start.context("start marker not found")?,
@@ -223,7 +175,7 @@ fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|| "input.txt".to_string());
     let map_str = std::fs::read_to_string(filename)?;
     let map = Map::from_str(&map_str).expect("failed to read map");
-    let solution = map.bfs().expect("failed to find route");
+    let solution = map.climb().expect("failed to find route");
     print_path2(&map, &solution)?;
     println!("{}", solution.len() - 1);
     Ok(())
@@ -237,7 +189,7 @@ mod test {
     fn example_test() {
         let example = include_str!("../../example.txt");
         let map = Map::from_str(example).expect("failed to read map");
-        let solution = map.bfs().expect("failed to find route");
+        let solution = map.climb().expect("failed to find route");
         dbg!(&solution);
         print_path2(&map, &solution).unwrap();
         assert_eq!(solution.first().copied(), Some((0, 0)));
@@ -249,7 +201,7 @@ mod test {
     fn input_test() {
         let example = include_str!("../../input.txt");
         let map = Map::from_str(example).expect("failed to read map");
-        let solution = map.bfs().expect("failed to find route");
+        let solution = map.climb().expect("failed to find route");
         dbg!(&solution);
         print_path2(&map, &solution).unwrap();
         assert_eq!(solution.len() - 1, 472);
@@ -0,0 +1,374 @@
+//! A rectangular grid of cells shared by the puzzles that read a map of characters.
+use anyhow::anyhow as e;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// `(x, y)` with the top left cell at `(0, 0)`
+pub type Coord = (usize, usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction {
+    /// The four directions sharing an edge with a cell
+    pub const ORTHOGONAL: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+    /// All eight directions, clockwise from north
+    pub const ALL: [Direction; 8] = [
+        Direction::N,
+        Direction::NE,
+        Direction::E,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+    ];
+
+    /// How far a single step in this direction moves along x and y, north is up
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::N => (0, -1),
+            Direction::NE => (1, -1),
+            Direction::E => (1, 0),
+            Direction::SE => (1, 1),
+            Direction::S => (0, 1),
+            Direction::SW => (-1, 1),
+            Direction::W => (-1, 0),
+            Direction::NW => (-1, -1),
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::N => Direction::S,
+            Direction::NE => Direction::SW,
+            Direction::E => Direction::W,
+            Direction::SE => Direction::NW,
+            Direction::S => Direction::N,
+            Direction::SW => Direction::NE,
+            Direction::W => Direction::E,
+            Direction::NW => Direction::SE,
+        }
+    }
+}
+
+pub fn signed_add(a: usize, b: isize) -> Option<usize> {
+    let neg_a: isize = -(a as isize);
+    if b < neg_a {
+        None
+    } else if b < 0 {
+        Some(a - b.unsigned_abs())
+    } else {
+        Some(a + b.unsigned_abs())
+    }
+}
+
+/// The coordinate one step from `coord` in `direction`, if it is not off the top or left edge
+pub fn step(coord: Coord, direction: Direction) -> Option<Coord> {
+    let (dx, dy) = direction.delta();
+    Some((signed_add(coord.0, dx)?, signed_add(coord.1, dy)?))
+}
+
+/// Cells stored row by row in a single `Vec`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from cells listed row by row
+    pub fn from_vec(width: usize, cells: Vec<T>) -> anyhow::Result<Self> {
+        let height = cells.len().checked_div(width).unwrap_or_default();
+        if width * height != cells.len() {
+            return Err(e!("{} cells do not fill rows of {}", cells.len(), width));
+        }
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Parse a grid with a character per cell, one row per line, converting every character
+    /// with `cell`. Trailing whitespace on a line is ignored and every row must be as wide as
+    /// the first.
+    pub fn parse<F>(s: &str, mut cell: F) -> anyhow::Result<Self>
+    where
+        F: FnMut(Coord, char) -> anyhow::Result<T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            let line = line.trim_end();
+            let before = cells.len();
+            for (x, c) in line.chars().enumerate() {
+                let value = cell((x, y), c)
+                    .map_err(|err| e!("line {} column {}: {}", y + 1, x + 1, err))?;
+                cells.push(value);
+            }
+            let row_width = cells.len() - before;
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    return Err(e!(
+                        "line {} has {} cells but the first line has {}",
+                        y + 1,
+                        row_width,
+                        width
+                    ))
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            cells,
+            width: width.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.0 < self.width && coord.1 < self.height
+    }
+
+    fn index_of(&self, coord: Coord) -> Option<usize> {
+        self.contains(coord)
+            .then_some(coord.1 * self.width + coord.0)
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.cells.get(self.index_of(coord)?)
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        let i = self.index_of(coord)?;
+        self.cells.get_mut(i)
+    }
+
+    /// The coordinate one step from `coord` in `direction` if it is inside the grid
+    pub fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        step(coord, direction).filter(|&next| self.contains(next))
+    }
+
+    /// The up to four cells sharing an edge with `coord`, clockwise from north
+    pub fn neighbours(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .filter_map(move |direction| self.step(coord, direction))
+    }
+
+    /// The up to eight cells touching `coord`, clockwise from north
+    pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(coord, direction))
+    }
+
+    /// Every cell from `start` to the edge of the grid in `direction`, starting with `start`
+    pub fn walk(&self, start: Coord, direction: Direction) -> Walk<'_, T> {
+        Walk {
+            grid: self,
+            next: Some(start),
+            direction,
+        }
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.rows().nth(y).into_iter().flatten()
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Every coordinate in the grid, row by row
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its coordinate, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> + '_ {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn map<U, F>(&self, mut f: F) -> Grid<U>
+    where
+        F: FnMut(Coord, &T) -> U,
+    {
+        Grid {
+            cells: self.iter().map(|(coord, cell)| f(coord, cell)).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord).unwrap_or_else(|| {
+            panic!(
+                "{:?} is outside a {}x{} grid",
+                coord, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", coord, width, height))
+    }
+}
+
+/// Each cell displayed one after another, one line per row
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator returned by [`Grid::walk`]
+pub struct Walk<'a, T> {
+    grid: &'a Grid<T>,
+    next: Option<Coord>,
+    direction: Direction,
+}
+
+impl<'a, T> Iterator for Walk<'a, T> {
+    type Item = (Coord, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let coord = self.next?;
+        let cell = self.grid.get(coord)?;
+        self.next = step(coord, self.direction);
+        Some((coord, cell))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digits(s: &str) -> Grid<u8> {
+        Grid::parse(s, |_, c| {
+            c.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or_else(|| e!("{:?} is not a digit", c))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signed_add() {
+        assert!(
+            signed_add(2, 1) == Some(3),
+            "positive input, positive output"
+        );
+        assert!(signed_add(2, 0) == Some(2), "zero input, positive output");
+        assert!(
+            signed_add(2, -1) == Some(1),
+            "negative input, positive output"
+        );
+        assert!(signed_add(2, -2) == Some(0), "negative input, zero output");
+        assert!(
+            signed_add(2, -3).is_none(),
+            "negative input, negative output"
+        );
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = digits("123\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get((2, 1)), Some(&6));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid[(0, 1)], 4);
+        assert_eq!(grid.to_string(), "123\n456");
+
+        let err = Grid::parse("12\n3x\n", |_, c| {
+            c.to_digit(10).ok_or_else(|| e!("{:?} is not a digit", c))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "line 2 column 2: 'x' is not a digit");
+        assert!(Grid::parse("12\n3\n", |_, c| Ok(c)).is_err());
+        assert_eq!(Grid::parse("", |_, c| Ok(c)).unwrap().width(), 0);
+    }
+
+    #[test]
+    fn test_neighbours_and_walks() {
+        let mut grid = digits("123\n456\n789");
+        let around: Vec<Coord> = grid.neighbours((0, 0)).collect();
+        assert_eq!(around, [(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8((2, 2)).count(), 3);
+
+        let walked: Vec<u8> = grid.walk((0, 2), Direction::NE).map(|(_, &d)| d).collect();
+        assert_eq!(walked, [7, 5, 3]);
+        assert_eq!(grid.walk((1, 1), Direction::W).count(), 2);
+        assert_eq!(grid.walk((5, 5), Direction::W).count(), 0);
+
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(grid.column(2).rev().copied().collect::<Vec<_>>(), [9, 6, 3]);
+        assert_eq!(grid.column(3).count(), 0);
+
+        grid[(1, 1)] = 0;
+        let doubled = grid.map(|_, &d| d * 2);
+        assert_eq!(doubled.to_string(), "246\n8012\n141618");
+        assert_eq!(Grid::from_vec(2, vec![1, 2, 3]).ok(), None);
+        assert_eq!(Grid::new(2, 1, 'x').to_string(), "xx");
+    }
+}